@@ -0,0 +1,25 @@
+//! Print each frame of a saved replay as text, along with the events of each tick
+//!
+//! ```sh
+//! cargo run --example replay --features serde -- game.replay
+//! ```
+
+use block_breaker::Replay;
+use std::error::Error;
+use std::{env, fs};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let path = env::args().nth(1).ok_or("usage: replay <file>")?;
+    let replay = Replay::load(&fs::read_to_string(path)?)?;
+    let mut game = replay.new_game();
+    println!("tick 0\n{}", game.render());
+    for (tick, input) in replay.inputs().iter().enumerate() {
+        input.apply(&mut game);
+        game.tick();
+        println!("tick {}\n{}", tick + 1, game.render());
+        for event in game.drain_events() {
+            println!("{:?}", event);
+        }
+    }
+    Ok(())
+}
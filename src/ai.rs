@@ -0,0 +1,230 @@
+use crate::ball::Ball;
+use crate::gamestate::GameState;
+use crate::replay::Input;
+use crate::state::State;
+
+/// Which way the bar should move for a tick
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BarMove {
+    /// Drive the bar left at full speed
+    Left,
+    /// Drive the bar right at full speed
+    Right,
+    /// Let the bar slow down and stop
+    Stay,
+}
+
+impl BarMove {
+    /// Set the input of the bar of the game, which moves it when the game ticks
+    pub fn apply(self, game: &mut GameState) {
+        self.input().apply(game)
+    }
+
+    /// Get the input that makes this move
+    pub fn input(self) -> Input {
+        Input {
+            left: self == BarMove::Left,
+            right: self == BarMove::Right,
+            ..Input::default()
+        }
+    }
+}
+
+/// A computer player that moves the bar to catch the balls
+///
+/// The autopilot predicts where each falling ball will reach the row of the bar, bouncing off the
+/// walls on the way but ignoring blocks, and moves the bar under the ball that gets there first.
+/// While no balls are falling the bar follows the lowest ball, as it could hit a block and come
+/// back down at any time.
+#[derive(Clone, Debug)]
+pub struct Autopilot {
+    /// Whether to hit the ball off-centre, sending it towards the blocks that are left rather
+    /// than straight back up
+    pub aim: bool,
+}
+
+impl Default for Autopilot {
+    fn default() -> Autopilot {
+        Autopilot { aim: true }
+    }
+}
+
+impl Autopilot {
+    /// Create a new autopilot that aims the ball
+    pub fn new() -> Autopilot {
+        Autopilot::default()
+    }
+
+    /// Predict the x coordinate where the next falling ball will reach the row of the bar
+    ///
+    /// Returns `None` if no balls are falling, e.g. they are all going up or stuck to the bar.
+    pub fn predict(&self, game: &GameState) -> Option<f64> {
+        let bar_y = f64::from(game.bar().y());
+        let width = f64::from(game.width());
+        game.balls()
+            .iter()
+            .filter(|ball| ball.stuck_offset().is_none())
+            .filter_map(|ball| predict_ball(ball, bar_y, width))
+            .fold(None, |first: Option<(f64, f64)>, (time, x)| match first {
+                Some((first_time, _)) if first_time <= time => first,
+                _ => Some((time, x)),
+            })
+            .map(|(_, x)| x)
+    }
+
+    /// Decide which way the bar should move this tick
+    pub fn decide(&self, game: &GameState) -> BarMove {
+        let target = match self.bar_target(game) {
+            Some(x) => x,
+            None => return BarMove::Stay,
+        };
+        let bar = game.bar();
+        let centre = bar.exact_x() + f64::from(bar.width()) / 2.;
+        if target < centre - 0.5 {
+            BarMove::Left
+        } else if target > centre + 0.5 {
+            BarMove::Right
+        } else {
+            BarMove::Stay
+        }
+    }
+
+    /// Get the input to give the game this tick, moving the bar towards where it should be and
+    /// launching the ball if it is being served
+    pub fn input(&self, game: &GameState) -> Input {
+        Input {
+            target: self.bar_target(game),
+            launch: game.state() == State::Serving
+                || game
+                    .balls()
+                    .iter()
+                    .any(|ball| ball.stuck_offset().is_some()),
+            ..Input::default()
+        }
+    }
+
+    /// Get where the centre of the bar should be to hit the next ball, or `None` if there are no
+    /// balls moving
+    fn bar_target(&self, game: &GameState) -> Option<f64> {
+        let lowest = game
+            .balls()
+            .iter()
+            .filter(|ball| ball.stuck_offset().is_none())
+            .max_by(|a, b| a.position().exact_y().total_cmp(&b.position().exact_y()))
+            .map(|ball| ball.position().exact_x());
+        let target = self.predict(game).or(lowest)?;
+        if self.aim {
+            // hitting the ball right of the centre sends it right, so put the bar on the other
+            // side of the ball from the blocks
+            if let Some(blocks_x) = breakable_blocks_x(game) {
+                let aim = f64::from(game.bar().width()) / 4.;
+                return Some(if blocks_x > target {
+                    target - aim
+                } else {
+                    target + aim
+                });
+            }
+        }
+        Some(target)
+    }
+}
+
+/// Predict how long a falling ball takes to reach the row at `bar_y` and the x coordinate it
+/// reaches it at, bouncing off the sides of a game `width` wide
+fn predict_ball(ball: &Ball, bar_y: f64, width: f64) -> Option<(f64, f64)> {
+    let position = ball.position();
+    let (x, y) = (position.exact_x(), position.exact_y());
+    let velocity = ball.velocity();
+    let (dx, dy) = (velocity.x(), velocity.y());
+    if dy <= 0. {
+        return None;
+    }
+    let time = (bar_y - y).max(0.) / dy;
+    // unfold the bounces off the sides
+    let x = (x + dx * time).rem_euclid(2. * width);
+    let x = if x > width { 2. * width - x } else { x };
+    Some((time / ball.speed(), x))
+}
+
+/// Get the average x coordinate of the blocks that can be destroyed
+fn breakable_blocks_x(game: &GameState) -> Option<f64> {
+    let centres: Vec<_> = game
+        .blocks()
+        .iter()
+        .filter(|block| block.kind().is_breakable())
+        .map(|block| f64::from(block.x()) + f64::from(block.width()) / 2.)
+        .collect();
+    if centres.is_empty() {
+        None
+    } else {
+        Some(centres.iter().sum::<f64>() / centres.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::level::Level;
+    use crate::position::Position;
+    use crate::vector::Vector;
+
+    #[test]
+    fn predict_bounces_off_walls() {
+        let mut game = GameState::from_seed(20, 20, &Config::new(0., 1, 4, 1), 1);
+        game.launch();
+        // the bar is on row 18, so the ball travels 10 rows down and 10 columns sideways,
+        // bouncing off the side walls
+        game.balls_mut()[0].set_position(Position::from_exact(15., 8.));
+        game.balls_mut()[0].set_velocity(Vector::new(1., 1.));
+        let x = Autopilot::new().predict(&game).unwrap();
+        assert!((x - 15.).abs() < 1e-9);
+
+        game.balls_mut()[0].set_position(Position::from_exact(3., 8.));
+        game.balls_mut()[0].set_velocity(Vector::new(-1., 1.));
+        let x = Autopilot::new().predict(&game).unwrap();
+        assert!((x - 7.).abs() < 1e-9);
+
+        game.balls_mut()[0].set_velocity(Vector::new(-1., -1.));
+        assert_eq!(Autopilot::new().predict(&game), None);
+    }
+
+    #[test]
+    fn decide_moves_under_the_ball() {
+        // a block out of the ball's way keeps the level from being cleared
+        let level = Level::parse("1").unwrap();
+        let mut game =
+            GameState::from_level_with_seed(20, 20, &Config::new(0., 1, 4, 1), &level, 1);
+        game.launch();
+        game.balls_mut()[0].set_position(Position::from_exact(2., 8.));
+        // going up first gives the bar time to speed up and get under the ball
+        game.balls_mut()[0].set_velocity(Vector::new(0., -1.));
+        let autopilot = Autopilot { aim: false };
+        assert_eq!(autopilot.decide(&game), BarMove::Left);
+        for _ in 0..20 {
+            autopilot.decide(&game).apply(&mut game);
+            game.tick()
+        }
+        assert_eq!(game.bar().x(), 0);
+        assert_eq!(autopilot.decide(&game), BarMove::Stay);
+    }
+
+    #[test]
+    fn autopilot_keeps_the_ball_in_play() {
+        // the bar moves at most `bar_max_speed` units a tick, so the ball mustn't speed up enough
+        // to outrun it
+        let mut config = Config::new(0.3, 2, 9, 1);
+        config.max_ball_speed = config.ball_speed;
+        let mut game = GameState::from_seed(60, 40, &config, 7);
+        let autopilot = Autopilot::new();
+        for _ in 0..3000 {
+            autopilot.input(&game).apply(&mut game);
+            game.tick();
+            game.drain_events();
+            assert_ne!(game.state(), State::LifeLost);
+            if game.state() == State::LevelCleared {
+                break;
+            }
+        }
+    }
+}
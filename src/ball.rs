@@ -1,12 +1,19 @@
+use crate::checksum::{Checksum, Fnv1a};
 use crate::dimensions::Dimensions;
 use crate::position::Position;
 use crate::vector::Vector;
 
 /// The ball
+///
+/// The ball travels in the direction of its velocity, covering `speed` units each tick.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ball {
     position: Position,
+    previous: Position,
     velocity: Vector,
+    speed: f64,
     power: u16,
+    stuck: Option<f64>,
     game_dimensions: Dimensions,
 }
 
@@ -16,9 +23,12 @@ impl Ball {
     /// The power of a ball is the amount of damage it does to blocks when it collides
     pub fn new(position: Position, game_dimensions: Dimensions, power: u16) -> Ball {
         Ball {
+            previous: position.clone(),
             position,
             velocity: Vector::new(0., 1.),
+            speed: 1.,
             power,
+            stuck: None,
             game_dimensions,
         }
     }
@@ -33,6 +43,95 @@ impl Ball {
         self.position.y()
     }
 
+    /// Get the exact x coordinate of the ball, for drawing it between cells
+    pub fn exact_x(&self) -> f64 {
+        self.position.exact_x()
+    }
+
+    /// Get the exact y coordinate of the ball, for drawing it between cells
+    pub fn exact_y(&self) -> f64 {
+        self.position.exact_y()
+    }
+
+    /// Get the position of the ball
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// Set the position of the ball
+    ///
+    /// The ball is teleported, so it isn't interpolated from where it was before.
+    pub fn set_position(&mut self, position: Position) {
+        self.previous = position.clone();
+        self.position = position
+    }
+
+    /// Move the ball along its path during a tick, so it is interpolated from where it was at the
+    /// start of the tick
+    pub(crate) fn move_to(&mut self, position: Position) {
+        self.position = position
+    }
+
+    /// Get the position of the ball `alpha` of the way from where it was at the start of the last
+    /// tick to where it is now
+    ///
+    /// This is for drawing the ball between ticks, with `alpha` from
+    /// `GameState::interpolation`.
+    pub fn interpolate(&self, alpha: f64) -> (f64, f64) {
+        let (x, y) = (self.position.exact_x(), self.position.exact_y());
+        let (previous_x, previous_y) = (self.previous.exact_x(), self.previous.exact_y());
+        (
+            previous_x + (x - previous_x) * alpha,
+            previous_y + (y - previous_y) * alpha,
+        )
+    }
+
+    /// Remember the current position as where the ball was at the start of a tick
+    pub(crate) fn remember_position(&mut self) {
+        self.previous = self.position.clone()
+    }
+
+    /// Set the direction the ball is travelling in
+    pub fn set_velocity(&mut self, mut velocity: Vector) {
+        velocity.normalise();
+        self.velocity = velocity
+    }
+
+    /// Get the direction the ball is travelling in
+    pub fn velocity(&self) -> &Vector {
+        &self.velocity
+    }
+
+    /// Get the distance the ball travels each tick
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Set the distance the ball travels each tick
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed
+    }
+
+    /// Increase the speed of the ball by `amount`, without going over `max_speed`
+    pub fn accelerate(&mut self, amount: f64, max_speed: f64) {
+        self.speed = (self.speed + amount).min(max_speed).max(self.speed)
+    }
+
+    /// Stick the ball to the bar, `offset` from the left of the bar
+    pub fn stick(&mut self, offset: f64) {
+        self.stuck = Some(offset)
+    }
+
+    /// Release the ball from the bar
+    pub fn unstick(&mut self) {
+        self.stuck = None
+    }
+
+    /// Get how far from the left of the bar the ball is stuck, if it is stuck to the bar
+    pub fn stuck_offset(&self) -> Option<f64> {
+        self.stuck
+    }
+
     /// Get the power of the ball
     pub fn power(&self) -> u16 {
         self.power
@@ -42,14 +141,17 @@ impl Ball {
     pub fn update_dimensions(&mut self, dimensions: Dimensions) {
         self.game_dimensions = dimensions;
         if self.x() >= self.game_dimensions.width() {
-            self.position = Position::new(self.game_dimensions.width() - 1, self.y())
+            self.position = Position::new(self.game_dimensions.width().saturating_sub(1), self.y())
         }
         if self.y() >= self.game_dimensions.height() {
-            self.position = Position::new(self.x(), self.game_dimensions.height() - 1)
+            self.position = Position::new(self.x(), self.game_dimensions.height().saturating_sub(1))
         }
     }
 
     /// Make the ball take a step
+    #[deprecated(
+        note = "use `GameState::tick`, which sweeps balls against the walls, bar and blocks"
+    )]
     pub fn tick(&mut self) {
         let mut new_position = &self.position + &self.velocity;
 
@@ -59,7 +161,7 @@ impl Ball {
         if new_position.y() >= self.game_dimensions.height() {
             new_position = Position::new(new_position.x(), self.game_dimensions.height())
         }
-        if new_position.y() >= self.game_dimensions.height() - 1 {
+        if new_position.y() >= self.game_dimensions.height().saturating_sub(1) {
             self.velocity = Vector::new(0., 0.)
         }
         self.position = new_position
@@ -72,6 +174,18 @@ impl Ball {
     }
 }
 
+/// Leaves out the position at the start of the last tick as it is only used for drawing
+impl Checksum for Ball {
+    fn checksum(&self, checksum: &mut Fnv1a) {
+        self.position.checksum(checksum);
+        self.velocity.checksum(checksum);
+        checksum.write_f64(self.speed);
+        checksum.write_u16(self.power);
+        checksum.write_option_f64(self.stuck);
+        self.game_dimensions.checksum(checksum);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -85,4 +199,26 @@ mod tests {
         ball.bounce(Vector::new(0., -1.));
         assert_eq!(ball.velocity, Vector::new(0., -1.));
     }
+
+    #[test]
+    fn accelerate_is_capped() {
+        let mut ball = Ball::new(Position::new(0, 0), Dimensions::new(100, 100), 1);
+
+        ball.accelerate(0.5, 2.);
+        assert_eq!(ball.speed(), 1.5);
+        ball.accelerate(1., 2.);
+        assert_eq!(ball.speed(), 2.);
+
+        // a ball already over the maximum isn't slowed down
+        ball.set_speed(3.);
+        ball.accelerate(1., 2.);
+        assert_eq!(ball.speed(), 3.);
+    }
+
+    #[test]
+    fn exact_position() {
+        let ball = Ball::new(Position::from_exact(2.75, 3.5), Dimensions::new(10, 10), 1);
+        assert_eq!((ball.x(), ball.y()), (2, 3));
+        assert_eq!((ball.exact_x(), ball.exact_y()), (2.75, 3.5));
+    }
 }
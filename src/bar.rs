@@ -1,14 +1,26 @@
+use crate::checksum::{Checksum, Fnv1a};
 use crate::dimensions::Dimensions;
 use crate::position::Position;
+use crate::vector::Vector;
+
+/// Speeds slower than this stop the bar, so friction doesn't leave it creeping forever
+const MIN_SPEED: f64 = 1e-3;
 
 /// The user controlled bar
 ///
 /// Restricted to moving left and right. The ball bounces off this at varying angles depending on
 /// the position.
+///
+/// The bar can be moved a unit at a time with `move_left` and `move_right`, or driven each tick
+/// by an analog input with `set_input` or towards a point with `move_to`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bar {
     dimensions: Dimensions,
     position: Position,
     game_dimensions: Dimensions,
+    velocity: f64,
+    input: f64,
+    target: Option<f64>,
 }
 
 impl Bar {
@@ -18,6 +30,9 @@ impl Bar {
             dimensions,
             position,
             game_dimensions: game_dimensions.clone(),
+            velocity: 0.,
+            input: 0.,
+            target: None,
         };
         // enforce constraints on the position
         player_bar.update_dimensions(game_dimensions);
@@ -26,30 +41,137 @@ impl Bar {
 
     /// Move the bar one unit left
     pub fn move_left(&mut self) {
-        if self.x() > 0 {
-            self.position -= Position::new(1, 0)
-        }
+        self.set_exact_x(self.exact_x() - 1.);
     }
 
     /// Move the bar one unit right
     pub fn move_right(&mut self) {
-        if self.x() + self.width() < self.game_dimensions.width() {
-            self.position += Position::new(1, 0)
-        }
+        self.set_exact_x(self.exact_x() + 1.);
+    }
+
+    /// Set the analog input driving the bar, from -1 for full speed left to 1 for full speed
+    /// right
+    ///
+    /// The input accelerates the bar each tick until it is changed, and stops the bar moving
+    /// towards the point given to `move_to`.
+    pub fn set_input(&mut self, input: f64) {
+        self.input = if input.is_nan() {
+            0.
+        } else {
+            input.clamp(-1., 1.)
+        };
+        self.target = None
+    }
+
+    /// Get the analog input driving the bar
+    pub fn input(&self) -> f64 {
+        self.input
+    }
+
+    /// Move the centre of the bar towards `x` each tick, e.g. to follow a mouse or touch
+    ///
+    /// The bar moves at up to its maximum speed until it gets there, then follows `x` until the
+    /// input is set.
+    pub fn move_to(&mut self, x: f64) {
+        self.target = Some(x);
+        self.input = 0.
+    }
+
+    /// Get the point the centre of the bar is moving towards, if it was given one by `move_to`
+    pub fn target(&self) -> Option<f64> {
+        self.target
+    }
+
+    /// Get the distance the bar moved in the last tick, negative when moving left
+    pub fn velocity(&self) -> f64 {
+        self.velocity
+    }
+
+    /// Move the bar for a tick of the game
+    ///
+    /// When following a target the bar moves straight towards it, otherwise the input
+    /// accelerates the bar and friction slows it down. Either way the bar moves at most
+    /// `max_speed` units, and stops when it hits the side of the game.
+    pub(crate) fn tick(&mut self, acceleration: f64, friction: f64, max_speed: f64) {
+        let x = self.exact_x();
+        let velocity = match self.target {
+            Some(target) => target - f64::from(self.width()) / 2. - x,
+            None => (self.velocity + self.input * acceleration) * (1. - friction),
+        };
+        let velocity = velocity.clamp(-max_speed, max_speed);
+        self.velocity = if velocity.abs() < MIN_SPEED {
+            0.
+        } else {
+            velocity
+        };
+        self.set_exact_x(x + self.velocity);
+        self.velocity = self.exact_x() - x
+    }
+
+    /// Move the left end of the bar to `x`, keeping the bar inside the game
+    pub(crate) fn set_exact_x(&mut self, x: f64) {
+        let max_x = f64::from(self.game_dimensions.width() - self.width());
+        self.position = Position::from_exact(x.clamp(0., max_x), self.exact_y());
     }
 
     /// Update the game dimensions stored in the bar
-    /// This clamps the bar's position if it would be outside the game on resize
+    /// This clamps the bar's position if it would be outside the game on resize, and narrows the
+    /// bar if the game is narrower than it
     pub fn update_dimensions(&mut self, dimensions: Dimensions) {
-        let mut new_position = Position::new(self.x(), dimensions.height() - 2);
-        if self.x() + self.width() >= dimensions.width() {
-            new_position = Position::new(dimensions.width() - self.width(), new_position.y());
-        }
-        self.position = new_position;
+        let width = self.width().min(dimensions.width());
+        let x = self.exact_x().min(f64::from(dimensions.width() - width));
+        let y = dimensions.height().saturating_sub(2);
+        self.dimensions = Dimensions::new(width, self.height());
+        self.position = Position::from_exact(x, f64::from(y));
 
         self.game_dimensions = dimensions;
     }
 
+    /// Whether the bar fits in a game `width` wide, and in the game it was last resized to
+    #[cfg(feature = "serde")]
+    pub(crate) fn fits(&self, width: u16) -> bool {
+        self.width() <= width && self.width() <= self.game_dimensions.width()
+    }
+
+    /// Change the width of the bar, keeping it centred where it was
+    ///
+    /// The bar is kept inside the game so can't be wider than it.
+    pub fn set_width(&mut self, width: u16) {
+        let game_width = self.game_dimensions.width();
+        let width = width.min(game_width);
+        let centre = self.x() + self.width() / 2;
+        let x = centre.saturating_sub(width / 2).min(game_width - width);
+        self.dimensions = Dimensions::new(width, self.height());
+        self.position = Position::new(x, self.y());
+    }
+
+    /// Get the direction a ball leaves the bar in when it hits the bar at `x`, with spin
+    ///
+    /// As with `bounce_direction`, but the angle is also turned `spin` degrees for each unit the
+    /// bar moved in the last tick, still up to `max_angle` degrees from vertical.
+    pub fn bounce_direction_with_spin(&self, x: f64, max_angle: f64, spin: f64) -> Vector {
+        let angle = self.hit_offset(x) * max_angle + self.velocity * spin;
+        let angle = angle.clamp(-max_angle, max_angle).to_radians();
+        Vector::new(angle.sin(), -angle.cos())
+    }
+
+    /// Get how far from the centre of the bar `x` is
+    ///
+    /// The offset is -1 at the left end of the bar, 0 in the centre and 1 at the right end.
+    pub fn hit_offset(&self, x: f64) -> f64 {
+        let half_width = f64::from(self.width()) / 2.;
+        let centre = self.position.exact_x() + half_width;
+        ((x - centre) / half_width).clamp(-1., 1.)
+    }
+
+    /// Get the direction a ball leaves the bar in when it hits the bar at `x`
+    ///
+    /// A ball hitting the centre of the bar goes straight up, the further from the centre it hits
+    /// the greater the angle, up to `max_angle` degrees from vertical at the ends of the bar.
+    pub fn bounce_direction(&self, x: f64, max_angle: f64) -> Vector {
+        self.bounce_direction_with_spin(x, max_angle, 0.)
+    }
+
     /// Get the x coordinate of the bar
     pub fn x(&self) -> u16 {
         self.position.x()
@@ -60,6 +182,16 @@ impl Bar {
         self.position.y()
     }
 
+    /// Get the exact x coordinate of the left end of the bar, for drawing it between cells
+    pub fn exact_x(&self) -> f64 {
+        self.position.exact_x()
+    }
+
+    /// Get the exact y coordinate of the top of the bar, for drawing it between cells
+    pub fn exact_y(&self) -> f64 {
+        self.position.exact_y()
+    }
+
     /// Get the height of the bar
     pub fn height(&self) -> u16 {
         self.dimensions.height()
@@ -71,6 +203,17 @@ impl Bar {
     }
 }
 
+impl Checksum for Bar {
+    fn checksum(&self, checksum: &mut Fnv1a) {
+        self.dimensions.checksum(checksum);
+        self.position.checksum(checksum);
+        self.game_dimensions.checksum(checksum);
+        checksum.write_f64(self.velocity);
+        checksum.write_f64(self.input);
+        checksum.write_option_f64(self.target);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,6 +254,89 @@ mod tests {
         assert_eq!(player_bar.position.x(), 5);
     }
 
+    #[test]
+    fn test_bounce_direction() {
+        let player_bar = Bar::new(
+            Position::new(0, 0),
+            Dimensions::new(4, 1),
+            Dimensions::new(10, 10),
+        );
+
+        assert_eq!(player_bar.bounce_direction(2., 60.), Vector::new(0., -1.));
+        let right = player_bar.bounce_direction(3., 60.);
+        assert!((right.x() - 30f64.to_radians().sin()).abs() < 1e-9);
+        assert!(right.y() < 0.);
+        let left = player_bar.bounce_direction(-5., 60.);
+        assert!((left.x() + 60f64.to_radians().sin()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_input_accelerates_to_max_speed() {
+        let mut player_bar = Bar::new(
+            Position::new(0, 0),
+            Dimensions::new(4, 1),
+            Dimensions::new(100, 10),
+        );
+
+        player_bar.set_input(1.);
+        player_bar.tick(0.5, 0.1, 1.);
+        assert!((player_bar.velocity() - 0.45).abs() < 1e-9);
+        assert!((player_bar.exact_x() - 0.45).abs() < 1e-9);
+        for _ in 0..10 {
+            player_bar.tick(0.5, 0.1, 1.);
+        }
+        assert_eq!(player_bar.velocity(), 1.);
+
+        // friction slows the bar to a stop once the input is released
+        player_bar.set_input(0.);
+        for _ in 0..100 {
+            player_bar.tick(0.5, 0.1, 1.);
+        }
+        assert_eq!(player_bar.velocity(), 0.);
+
+        // the bar stops dead at the side of the game
+        player_bar.set_input(-1.);
+        for _ in 0..100 {
+            player_bar.tick(0.5, 0.1, 1.);
+        }
+        assert_eq!((player_bar.exact_x(), player_bar.velocity()), (0., 0.));
+    }
+
+    #[test]
+    fn test_move_to() {
+        let mut player_bar = Bar::new(
+            Position::new(0, 0),
+            Dimensions::new(4, 1),
+            Dimensions::new(100, 10),
+        );
+
+        player_bar.move_to(5.5);
+        player_bar.tick(0.5, 0.1, 2.);
+        assert_eq!(player_bar.exact_x(), 2.);
+        player_bar.tick(0.5, 0.1, 2.);
+        assert_eq!((player_bar.exact_x(), player_bar.velocity()), (3.5, 1.5));
+        player_bar.tick(0.5, 0.1, 2.);
+        assert_eq!((player_bar.exact_x(), player_bar.velocity()), (3.5, 0.));
+
+        // setting the input stops following the target
+        player_bar.set_input(0.);
+        assert_eq!(player_bar.target(), None);
+    }
+
+    #[test]
+    fn test_set_width_stays_in_game() {
+        let mut player_bar = Bar::new(
+            Position::new(4, 0),
+            Dimensions::new(2, 1),
+            Dimensions::new(10, 10),
+        );
+
+        player_bar.set_width(4);
+        assert_eq!((player_bar.x(), player_bar.width()), (3, 4));
+        player_bar.set_width(20);
+        assert_eq!((player_bar.x(), player_bar.width()), (0, 10));
+    }
+
     #[test]
     fn test_move_left_doesnt_go_over() {
         let mut player_bar = Bar::new(
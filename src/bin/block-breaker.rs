@@ -0,0 +1,319 @@
+//! A terminal frontend for the block breaker game
+//!
+//! Arrow keys move the bar, space launches the ball, p pauses and q quits. The difficulty can be
+//! given as the first argument, e.g. `block-breaker hard`.
+
+use block_breaker::{BlockKind, Config, Difficulty, GameState, PowerUpKind, ResizePolicy, State};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Color, Print, ResetColor, SetForegroundColor};
+use crossterm::terminal::{
+    self, disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+    LeaveAlternateScreen,
+};
+use crossterm::{execute, queue};
+use std::env;
+use std::error::Error;
+use std::io::{self, Stdout, Write};
+use std::time::{Duration, Instant};
+
+/// How long each key press drives the bar for, terminals don't report key releases so a held key
+/// is seen as repeated presses
+const KEY_HOLD: Duration = Duration::from_millis(150);
+/// The smallest game that can be played, smaller terminals show a message instead
+const MIN_WIDTH: u16 = 20;
+const MIN_HEIGHT: u16 = 10;
+/// Colours for blocks by strength, stronger blocks use later colours
+const STRENGTH_COLOURS: [Color; 6] = [
+    Color::Green,
+    Color::Cyan,
+    Color::Blue,
+    Color::Magenta,
+    Color::Yellow,
+    Color::Red,
+];
+
+/// Puts the terminal back how it was when dropped, even if the game panics
+struct Terminal {
+    stdout: Stdout,
+}
+
+impl Terminal {
+    fn new() -> io::Result<Terminal> {
+        let mut stdout = io::stdout();
+        enable_raw_mode()?;
+        execute!(stdout, EnterAlternateScreen, Hide)?;
+        Ok(Terminal { stdout })
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = execute!(self.stdout, ResetColor, Show, LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+    }
+}
+
+/// What to do after handling a key
+enum Action {
+    Continue,
+    Quit,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let difficulty = match env::args().nth(1) {
+        Some(name) => name.parse()?,
+        None => Difficulty::Normal,
+    };
+    let config = Config::preset(difficulty);
+    let mut terminal = Terminal::new()?;
+    let mut size = terminal::size()?;
+    // start at the smallest size if the terminal is too small, until it is made bigger
+    let (width, height) = game_size(size).unwrap_or((MIN_WIDTH, MIN_HEIGHT));
+    let mut game = new_game(width, height, &config);
+    draw(&mut terminal.stdout, &game, size)?;
+
+    let mut last_advance = Instant::now();
+    // when to stop driving the bar, if a key is driving it
+    let mut release_at = None;
+    loop {
+        // wait for input until the next tick is due
+        let until_tick = game.tick_length().mul_f64(1. - game.interpolation());
+        let timeout = until_tick.saturating_sub(last_advance.elapsed());
+        if event::poll(timeout)? {
+            match event::read()? {
+                Event::Key(key) => {
+                    let action = if game_size(size).is_some() {
+                        if let KeyCode::Left | KeyCode::Right = key.code {
+                            release_at = Some(Instant::now() + KEY_HOLD)
+                        }
+                        handle_key(&mut game, &config, key)
+                    } else if is_quit(key) {
+                        Action::Quit
+                    } else {
+                        Action::Continue
+                    };
+                    if let Action::Quit = action {
+                        return Ok(());
+                    }
+                }
+                Event::Resize(columns, rows) => {
+                    size = (columns, rows);
+                    if let Some((width, height)) = game_size(size) {
+                        game.update_dimensions(width, height)
+                    }
+                    execute!(terminal.stdout, Clear(ClearType::All))?
+                }
+                _ => {}
+            }
+            draw(&mut terminal.stdout, &game, size)?;
+            continue;
+        }
+
+        let now = Instant::now();
+        if release_at.is_some_and(|release_at| now >= release_at) {
+            game.bar_mut().set_input(0.);
+            release_at = None
+        }
+        if game_size(size).is_none() {
+            // the game is hidden, so it stands still until the terminal is big enough again
+            last_advance = now;
+            continue;
+        }
+        let ticks = game.advance(now - last_advance);
+        last_advance = now;
+        if ticks > 0 {
+            // nothing in the terminal reacts to events yet, so drop them rather than let them
+            // build up
+            game.drain_events();
+            draw(&mut terminal.stdout, &game, size)?;
+        }
+    }
+}
+
+/// Start a new game that keeps the blocks centred when the terminal is resized
+fn new_game(width: u16, height: u16, config: &Config) -> GameState {
+    let mut game = GameState::new(width, height, config);
+    game.set_resize_policy(ResizePolicy::Centre);
+    game
+}
+
+/// Get the size of the game for a terminal, leaving the top line for the status, or `None` if
+/// the terminal is too small to play in
+fn game_size((columns, rows): (u16, u16)) -> Option<(u16, u16)> {
+    let rows = rows.saturating_sub(1);
+    if columns < MIN_WIDTH || rows < MIN_HEIGHT {
+        None
+    } else {
+        Some((columns, rows))
+    }
+}
+
+/// Whether the key quits the game
+fn is_quit(key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => true,
+        KeyCode::Char('c') => key.modifiers.contains(KeyModifiers::CONTROL),
+        _ => false,
+    }
+}
+
+fn handle_key(game: &mut GameState, config: &Config, key: KeyEvent) -> Action {
+    if key.kind == KeyEventKind::Release {
+        return Action::Continue;
+    }
+    if is_quit(key) {
+        return Action::Quit;
+    }
+    match key.code {
+        KeyCode::Char('p') => game.toggle_pause(),
+        KeyCode::Left if !game.is_paused() => game.bar_mut().set_input(-1.),
+        KeyCode::Right if !game.is_paused() => game.bar_mut().set_input(1.),
+        KeyCode::Char(' ') => match game.state() {
+            State::LevelCleared => game.next_level(),
+            State::GameOver => *game = new_game(game.width(), game.height(), config),
+            _ => game.launch(),
+        },
+        _ => {}
+    }
+    Action::Continue
+}
+
+/// Get the colour to draw a block in
+fn block_colour(kind: BlockKind, strength: u16) -> Color {
+    match kind {
+        BlockKind::Wall => Color::DarkGrey,
+        _ => {
+            let index = usize::from(strength.max(1) - 1).min(STRENGTH_COLOURS.len() - 1);
+            STRENGTH_COLOURS[index]
+        }
+    }
+}
+
+/// Get the character to draw a block with
+fn block_char(kind: BlockKind) -> char {
+    match kind {
+        BlockKind::Normal => '#',
+        BlockKind::Wall => '█',
+        BlockKind::Explosive { .. } => 'X',
+        BlockKind::MultiHit => 'M',
+        BlockKind::Regenerating { .. } => 'R',
+        BlockKind::PowerUp => 'P',
+    }
+}
+
+/// Get the character to draw a falling power-up with
+fn power_up_char(kind: PowerUpKind) -> char {
+    match kind {
+        PowerUpKind::WideBar => 'W',
+        PowerUpKind::SlowBall => 'S',
+        PowerUpKind::ExtraLife => '+',
+        PowerUpKind::MultiBall => 'B',
+        PowerUpKind::PiercingBall => '!',
+        PowerUpKind::StickyBar => '@',
+    }
+}
+
+fn status(game: &GameState) -> String {
+    let message = match game.state() {
+        State::Serving => "space to launch",
+        State::Playing
+            if game
+                .balls()
+                .iter()
+                .any(|ball| ball.stuck_offset().is_some()) =>
+        {
+            "space to launch"
+        }
+        State::Playing => "",
+        State::Paused => "paused, p to resume",
+        State::LifeLost => "life lost",
+        State::LevelCleared => "level cleared, space for the next level",
+        State::GameOver => "game over, space to play again or q to quit",
+    };
+    format!(
+        "level {}  lives {}  score {}  x{}  {}",
+        game.level(),
+        game.lives(),
+        game.score(),
+        game.multiplier(),
+        message
+    )
+}
+
+/// Draw the game, with the status on the first line, or a message if the terminal of `size` is
+/// too small for the game
+fn draw(stdout: &mut Stdout, game: &GameState, size: (u16, u16)) -> io::Result<()> {
+    if game_size(size).is_none() {
+        let message = format!(
+            "terminal too small, make it at least {}x{}",
+            MIN_WIDTH,
+            MIN_HEIGHT + 1
+        );
+        let message: String = message.chars().take(usize::from(size.0)).collect();
+        queue!(
+            stdout,
+            Clear(ClearType::All),
+            MoveTo(0, 0),
+            ResetColor,
+            Print(message)
+        )?;
+        return stdout.flush();
+    }
+    let (width, height) = (usize::from(game.width()), usize::from(game.height()));
+    let mut cells = vec![vec![(' ', Color::Reset); width]; height];
+    let mut set = |x: u16, y: u16, cell: (char, Color)| {
+        if let Some(row) = cells.get_mut(usize::from(y)) {
+            if let Some(c) = row.get_mut(usize::from(x)) {
+                *c = cell
+            }
+        }
+    };
+
+    for block in game.blocks() {
+        let cell = (
+            block_char(block.kind()),
+            block_colour(block.kind(), block.strength()),
+        );
+        for x in block.x()..block.x() + block.width() {
+            for y in block.y()..block.y() + block.height() {
+                set(x, y, cell)
+            }
+        }
+    }
+    for power_up in game.power_ups() {
+        set(
+            power_up.x(),
+            power_up.y(),
+            (power_up_char(power_up.kind()), Color::White),
+        )
+    }
+    let bar = game.bar();
+    for x in bar.x()..bar.x() + bar.width() {
+        set(x, bar.y(), ('=', Color::White))
+    }
+    for ball in game.balls() {
+        set(ball.x(), ball.y(), ('o', Color::White))
+    }
+
+    queue!(
+        stdout,
+        MoveTo(0, 0),
+        ResetColor,
+        Print(status(game)),
+        Clear(ClearType::UntilNewLine)
+    )?;
+    for (y, row) in cells.iter().enumerate() {
+        queue!(stdout, MoveTo(0, y as u16 + 1))?;
+        let mut colour = None;
+        for &(character, cell_colour) in row {
+            if colour != Some(cell_colour) {
+                queue!(stdout, SetForegroundColor(cell_colour))?;
+                colour = Some(cell_colour)
+            }
+            queue!(stdout, Print(character))?;
+        }
+    }
+    queue!(stdout, ResetColor)?;
+    stdout.flush()
+}
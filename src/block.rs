@@ -1,25 +1,91 @@
+use crate::checksum::{Checksum, Fnv1a};
 use crate::dimensions::Dimensions;
 use crate::position::Position;
 
+/// The kind of a block, which changes how it reacts to being hit
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlockKind {
+    /// A block that loses the ball's power in strength each hit
+    Normal,
+    /// An indestructible wall, which doesn't need to be destroyed to clear the level
+    Wall,
+    /// A block that damages the blocks within `radius` cells of it when it is destroyed
+    Explosive {
+        /// How far from the block the explosion reaches
+        radius: u16,
+    },
+    /// A block that drops down one tier of strength each hit, however powerful the ball
+    MultiHit,
+    /// A block that heals back to its initial strength if it isn't hit again for `ticks` ticks
+    Regenerating {
+        /// The number of ticks before the block heals
+        ticks: u32,
+    },
+    /// A block that always drops a power-up when it is destroyed
+    PowerUp,
+}
+
+impl BlockKind {
+    /// Whether blocks of this kind can be destroyed, and so need to be to clear the level
+    pub fn is_breakable(self) -> bool {
+        self != BlockKind::Wall
+    }
+}
+
 /// A single block in the game
 ///
-/// Each block has a position, size and strength
+/// Each block has a position, size, strength and kind
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Block {
     position: Position,
+    origin: Position,
     dimensions: Dimensions,
     strength: u16,
+    initial_strength: u16,
+    kind: BlockKind,
+    ticks_since_hit: u32,
 }
 
 impl Block {
-    /// Create a new block
+    /// Create a new, normal, block
     pub fn new(position: Position, dimensions: Dimensions, strength: u16) -> Block {
+        Block::with_kind(position, dimensions, strength, BlockKind::Normal)
+    }
+
+    /// Create a new block of the given kind
+    pub fn with_kind(
+        position: Position,
+        dimensions: Dimensions,
+        strength: u16,
+        kind: BlockKind,
+    ) -> Block {
         Block {
+            origin: position.clone(),
             position,
             dimensions,
             strength,
+            initial_strength: strength,
+            kind,
+            ticks_since_hit: 0,
         }
     }
 
+    /// Get where the block was laid out at the start of the level, before any resizes
+    pub(crate) fn origin(&self) -> &Position {
+        &self.origin
+    }
+
+    /// Move the block, leaving where it was laid out as it was
+    pub(crate) fn set_position(&mut self, position: Position) {
+        self.position = position
+    }
+
+    /// Get the dimensions of the block
+    pub(crate) fn dimensions(&self) -> &Dimensions {
+        &self.dimensions
+    }
+
     /// Get the x coordinate of the block
     pub fn x(&self) -> u16 {
         self.position.x()
@@ -40,8 +106,21 @@ impl Block {
         self.dimensions.height()
     }
 
+    /// Get the kind of the block
+    pub fn kind(&self) -> BlockKind {
+        self.kind
+    }
+
     /// Damage the block, from a contact with the ball
+    ///
+    /// Walls are never damaged and multi-hit blocks only lose one point of strength.
     pub fn damage(&mut self, amount: u16) {
+        let amount = match self.kind {
+            BlockKind::Wall => 0,
+            BlockKind::MultiHit => amount.min(1),
+            _ => amount,
+        };
+        self.ticks_since_hit = 0;
         if amount > self.strength {
             self.strength = 0
         } else {
@@ -49,8 +128,123 @@ impl Block {
         }
     }
 
+    /// Whether the block has been destroyed
+    pub fn is_destroyed(&self) -> bool {
+        self.kind.is_breakable() && self.strength == 0
+    }
+
+    /// Whether the block is within `distance` cells of another block
+    pub fn is_near(&self, other: &Block, distance: u16) -> bool {
+        let gap = |start: u16, length: u16, other_start: u16, other_length: u16| {
+            if start + length <= other_start {
+                other_start - (start + length)
+            } else {
+                start.saturating_sub(other_start + other_length)
+            }
+        };
+        gap(self.x(), self.width(), other.x(), other.width()) <= distance
+            && gap(self.y(), self.height(), other.y(), other.height()) <= distance
+    }
+
+    /// Count down to a regenerating block healing
+    pub fn tick(&mut self) {
+        if let BlockKind::Regenerating { ticks } = self.kind {
+            if self.strength < self.initial_strength {
+                self.ticks_since_hit += 1;
+                if self.ticks_since_hit >= ticks {
+                    self.strength = self.initial_strength
+                }
+            }
+        }
+    }
+
     /// Get the strength of the block
+    ///
+    /// For multi-hit blocks this is the tier the block is on.
     pub fn strength(&self) -> u16 {
         self.strength
     }
+
+    /// Get the strength the block was created with
+    pub fn initial_strength(&self) -> u16 {
+        self.initial_strength
+    }
+}
+
+impl Checksum for BlockKind {
+    fn checksum(&self, checksum: &mut Fnv1a) {
+        match *self {
+            BlockKind::Normal => checksum.write_u8(0),
+            BlockKind::Wall => checksum.write_u8(1),
+            BlockKind::Explosive { radius } => {
+                checksum.write_u8(2);
+                checksum.write_u16(radius)
+            }
+            BlockKind::MultiHit => checksum.write_u8(3),
+            BlockKind::Regenerating { ticks } => {
+                checksum.write_u8(4);
+                checksum.write_u32(ticks)
+            }
+            BlockKind::PowerUp => checksum.write_u8(5),
+        }
+    }
+}
+
+impl Checksum for Block {
+    fn checksum(&self, checksum: &mut Fnv1a) {
+        self.position.checksum(checksum);
+        self.origin.checksum(checksum);
+        self.dimensions.checksum(checksum);
+        checksum.write_u16(self.strength);
+        checksum.write_u16(self.initial_strength);
+        self.kind.checksum(checksum);
+        checksum.write_u32(self.ticks_since_hit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(kind: BlockKind) -> Block {
+        Block::with_kind(Position::new(3, 2), Dimensions::new(3, 1), 3, kind)
+    }
+
+    #[test]
+    fn damage_depends_on_kind() {
+        let mut normal = block(BlockKind::Normal);
+        normal.damage(2);
+        assert_eq!(normal.strength(), 1);
+
+        let mut wall = block(BlockKind::Wall);
+        wall.damage(5);
+        assert_eq!(wall.strength(), 3);
+        assert!(!wall.is_destroyed());
+
+        let mut multi_hit = block(BlockKind::MultiHit);
+        multi_hit.damage(5);
+        assert_eq!(multi_hit.strength(), 2);
+    }
+
+    #[test]
+    fn regenerating_blocks_heal() {
+        let mut regenerating = block(BlockKind::Regenerating { ticks: 2 });
+        regenerating.tick();
+        regenerating.damage(1);
+        regenerating.tick();
+        assert_eq!(regenerating.strength(), 2);
+        regenerating.tick();
+        assert_eq!(regenerating.strength(), 3);
+    }
+
+    #[test]
+    fn nearby_blocks() {
+        let centre = block(BlockKind::Normal);
+        let touching = Block::new(Position::new(6, 3), Dimensions::new(3, 1), 1);
+        let apart = Block::new(Position::new(10, 2), Dimensions::new(3, 1), 1);
+        assert!(centre.is_near(&touching, 0));
+        assert!(!centre.is_near(&apart, 0));
+        assert!(centre.is_near(&apart, 4));
+        assert!(apart.is_near(&centre, 4));
+    }
 }
@@ -0,0 +1,91 @@
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0100_0000_01b3;
+
+/// A 64 bit FNV-1a checksum
+///
+/// Values are written as fixed width little endian bytes, with lengths as 64 bit integers, so the
+/// checksum of a game is the same on every platform and can be stored and compared later.
+pub struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Fnv1a {
+        Fnv1a(OFFSET_BASIS)
+    }
+}
+
+impl Fnv1a {
+    /// Get the checksum of everything written so far
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(PRIME);
+        }
+    }
+
+    /// Write a byte, used for enum variants and flags
+    pub fn write_u8(&mut self, value: u8) {
+        self.write(&[value])
+    }
+
+    /// Write a 16 bit integer
+    pub fn write_u16(&mut self, value: u16) {
+        self.write(&value.to_le_bytes())
+    }
+
+    /// Write a 32 bit integer
+    pub fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes())
+    }
+
+    /// Write a 64 bit integer
+    pub fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes())
+    }
+
+    /// Write the exact bits of a float
+    pub fn write_f64(&mut self, value: f64) {
+        self.write_u64(value.to_bits())
+    }
+
+    /// Write an optional float, with a flag for whether it is there
+    pub fn write_option_f64(&mut self, value: Option<f64>) {
+        match value {
+            Some(value) => {
+                self.write_u8(1);
+                self.write_f64(value)
+            }
+            None => self.write_u8(0),
+        }
+    }
+
+    /// Write a slice of values prefixed by its length
+    pub fn write_all<T: Checksum>(&mut self, values: &[T]) {
+        self.write_u64(values.len() as u64);
+        for value in values {
+            value.checksum(self)
+        }
+    }
+}
+
+/// Part of the state of a game that goes into its checksum
+pub trait Checksum {
+    /// Write the state into the checksum
+    fn checksum(&self, checksum: &mut Fnv1a);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum_is_fixed() {
+        let mut checksum = Fnv1a::default();
+        assert_eq!(checksum.finish(), OFFSET_BASIS);
+        checksum.write_u32(1);
+        assert_eq!(checksum.finish(), 0xad2a_ca77_4798_5764);
+    }
+}
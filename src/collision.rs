@@ -0,0 +1,213 @@
+use crate::vector::Vector;
+
+/// An axis aligned box that the ball can collide with
+///
+/// The bounds may be infinite, which is used for the walls of the game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Aabb {
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+}
+
+impl Aabb {
+    /// Create a new box from its minimum and maximum corners
+    pub fn new(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Aabb {
+        Aabb {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        }
+    }
+
+    /// Create the box for an item with the given position and size
+    pub fn from_cells(x: u16, y: u16, width: u16, height: u16) -> Aabb {
+        Aabb::new(
+            f64::from(x),
+            f64::from(y),
+            f64::from(x) + f64::from(width),
+            f64::from(y) + f64::from(height),
+        )
+    }
+
+    /// The space to the left of `x`
+    pub fn left_of(x: f64) -> Aabb {
+        Aabb::new(f64::NEG_INFINITY, f64::NEG_INFINITY, x, f64::INFINITY)
+    }
+
+    /// The space to the right of `x`
+    pub fn right_of(x: f64) -> Aabb {
+        Aabb::new(x, f64::NEG_INFINITY, f64::INFINITY, f64::INFINITY)
+    }
+
+    /// The space above `y`
+    pub fn above(y: f64) -> Aabb {
+        Aabb::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::INFINITY, y)
+    }
+
+    /// Get where a point inside the box leaves it by the shortest way out, along with the normal
+    /// of the face it leaves through
+    ///
+    /// Points on the edge of the box or outside it aren't inside, so give `None`.
+    pub fn push_out(&self, x: f64, y: f64) -> Option<(f64, f64, Vector)> {
+        if !(self.min_x < x && x < self.max_x && self.min_y < y && y < self.max_y) {
+            return None;
+        }
+        let exits = [
+            (x - self.min_x, self.min_x, y, Vector::new(-1., 0.)),
+            (self.max_x - x, self.max_x, y, Vector::new(1., 0.)),
+            (y - self.min_y, x, self.min_y, Vector::new(0., -1.)),
+            (self.max_y - y, x, self.max_y, Vector::new(0., 1.)),
+        ];
+        exits
+            .iter()
+            .cloned()
+            .fold(
+                None::<(f64, f64, f64, Vector)>,
+                |nearest, exit| match nearest {
+                    Some((distance, ..)) if distance <= exit.0 => nearest,
+                    _ => Some(exit),
+                },
+            )
+            .map(|(_, x, y, normal)| (x, y, normal))
+    }
+}
+
+/// Where a moving point first touches a box
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contact {
+    /// The fraction of the motion completed before the contact, between 0 and 1
+    pub time: f64,
+    /// The normal of the face that was hit, diagonal if the point hit a corner
+    pub normal: Vector,
+}
+
+impl Contact {
+    /// Whether the contact was with a corner rather than a face
+    pub fn is_corner(&self) -> bool {
+        self.normal.x() != 0. && self.normal.y() != 0.
+    }
+
+    /// Whether this contact should be handled before `other`
+    ///
+    /// Earlier contacts come first, faces come before corners at the same time.
+    pub fn before(&self, other: &Contact) -> bool {
+        self.time < other.time
+            || (self.time == other.time && !self.is_corner() && other.is_corner())
+    }
+}
+
+/// The times a point moving along one axis enters and exits a range, along with the sign of the
+/// normal of the side it enters through
+fn slab(position: f64, motion: f64, min: f64, max: f64) -> Option<(f64, f64, f64)> {
+    if motion == 0. {
+        if position < min || position > max {
+            None
+        } else {
+            Some((f64::NEG_INFINITY, f64::INFINITY, 0.))
+        }
+    } else if motion > 0. {
+        Some(((min - position) / motion, (max - position) / motion, -1.))
+    } else {
+        Some(((max - position) / motion, (min - position) / motion, 1.))
+    }
+}
+
+/// Sweep a point from `(x, y)` by `(dx, dy)` against a box, returning the earliest contact
+///
+/// Points that start inside the box, or never reach it during the motion, don't collide.
+pub fn sweep(x: f64, y: f64, dx: f64, dy: f64, aabb: &Aabb) -> Option<Contact> {
+    let (x_enter, x_exit, x_sign) = slab(x, dx, aabb.min_x, aabb.max_x)?;
+    let (y_enter, y_exit, y_sign) = slab(y, dy, aabb.min_y, aabb.max_y)?;
+    let enter = x_enter.max(y_enter);
+    let exit = x_exit.min(y_exit);
+    if enter > exit || !(0. ..=1.).contains(&enter) {
+        return None;
+    }
+    let normal = if x_enter > y_enter {
+        Vector::new(x_sign, 0.)
+    } else if y_enter > x_enter {
+        Vector::new(0., y_sign)
+    } else {
+        Vector::new(x_sign, y_sign)
+    };
+    Some(Contact {
+        time: enter,
+        normal,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block() -> Aabb {
+        Aabb::from_cells(10, 10, 3, 1)
+    }
+
+    #[test]
+    fn hits_faces() {
+        let top = sweep(11.5, 9.5, 0., 1., &block()).unwrap();
+        assert_eq!(top.time, 0.5);
+        assert_eq!(top.normal, Vector::new(0., -1.));
+
+        let bottom = sweep(11.5, 11.5, 0., -1., &block()).unwrap();
+        assert_eq!(bottom.normal, Vector::new(0., 1.));
+
+        let left = sweep(9.5, 10.5, 1., 0., &block()).unwrap();
+        assert_eq!(left.normal, Vector::new(-1., 0.));
+
+        let right = sweep(13.5, 10.5, -1., 0., &block()).unwrap();
+        assert_eq!(right.normal, Vector::new(1., 0.));
+    }
+
+    #[test]
+    fn hits_corners() {
+        let corner = sweep(9.5, 9.5, 1., 1., &block()).unwrap();
+        assert!(corner.is_corner());
+        assert_eq!(corner.normal, Vector::new(-1., -1.));
+        assert_eq!(corner.time, 0.5);
+    }
+
+    #[test]
+    fn fast_motion_does_not_tunnel() {
+        let contact = sweep(11.5, 5.5, 0., 20., &block()).unwrap();
+        assert_eq!(contact.time, 4.5 / 20.);
+    }
+
+    #[test]
+    fn misses() {
+        assert_eq!(sweep(11.5, 8.5, 0., 1., &block()), None);
+        assert_eq!(sweep(11.5, 9.5, 0., -1., &block()), None);
+        assert_eq!(sweep(9.5, 9.5, 0., 5., &block()), None);
+        assert_eq!(sweep(11.5, 10.5, 0., 1., &block()), None);
+    }
+
+    #[test]
+    fn pushes_out_the_nearest_face() {
+        assert_eq!(
+            block().push_out(10.4, 10.5),
+            Some((10., 10.5, Vector::new(-1., 0.)))
+        );
+        assert_eq!(
+            block().push_out(11.5, 10.75),
+            Some((11.5, 11., Vector::new(0., 1.)))
+        );
+        assert_eq!(block().push_out(11.5, 10.), None);
+        assert_eq!(block().push_out(11.5, 9.5), None);
+        assert_eq!(
+            Aabb::left_of(0.).push_out(-2., 5.),
+            Some((0., 5., Vector::new(1., 0.)))
+        );
+    }
+
+    #[test]
+    fn walls() {
+        let contact = sweep(0.5, 5., -1., 0., &Aabb::left_of(0.)).unwrap();
+        assert_eq!(contact.time, 0.5);
+        assert_eq!(contact.normal, Vector::new(1., 0.));
+        assert_eq!(sweep(0.5, 5., 1., 1., &Aabb::left_of(0.)), None);
+    }
+}
@@ -1,4 +1,20 @@
+use crate::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The amount the block density increases by for each level
+const LEVEL_DENSITY_STEP: f64 = 0.1;
+/// The number of levels between each increase in block strength
+const LEVELS_PER_STRENGTH: u16 = 2;
+/// The steepest bounce angle `Config::clamped` gives, the angle must be less than 90 degrees
+const MAX_BOUNCE_ANGLE: f64 = 89.;
+
 /// A struct for holding the config
+///
+/// When deserializing, missing fields are taken from the `Normal` difficulty.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
 pub struct Config {
     /// The density to generate blocks at
     pub block_density: f64,
@@ -8,16 +24,691 @@ pub struct Config {
     pub bar_width: u16,
     /// The strength of the ball
     pub ball_power: u16,
+    /// The number of lives the player starts with
+    pub lives: u16,
+    /// The largest angle from vertical, in degrees, that the ball leaves the bar at
+    pub max_bounce_angle: f64,
+    /// The distance the ball travels each tick when it is served
+    pub ball_speed: f64,
+    /// The fastest the ball can travel
+    pub max_ball_speed: f64,
+    /// How much the ball speeds up by each time it hits a block or `speed_up_interval` ticks pass
+    pub ball_acceleration: f64,
+    /// The number of ticks between the ball speeding up, 0 to only speed up on hitting blocks
+    pub speed_up_interval: u32,
+    /// The probability of a destroyed block dropping a power-up
+    pub power_up_chance: f64,
+    /// How likely each kind of power-up is to be dropped, in the order of `PowerUpKind::ALL`
+    ///
+    /// Each kind is dropped in proportion to its weight, so a kind with a weight of 0 is never
+    /// dropped.
+    pub power_up_weights: [f64; 6],
+    /// The distance a power-up falls each tick
+    pub power_up_fall_speed: f64,
+    /// The number of ticks the effects of timed power-ups last for
+    pub power_up_duration: u32,
+    /// The number of ticks in each second of game time, used by `GameState::advance`
+    pub ticks_per_second: u32,
+    /// The furthest the bar moves each tick
+    pub bar_max_speed: f64,
+    /// How much the bar speeds up each tick at full input
+    pub bar_acceleration: f64,
+    /// The fraction of the bar's speed lost each tick, from 0 to 1
+    pub bar_friction: f64,
+    /// The degrees the ball is turned leaving the bar for each unit the bar moved in the last
+    /// tick, 0 for no spin
+    pub bar_spin: f64,
+    /// Whether balls hitting the top of the bar stick to it until they are launched, as with the
+    /// `StickyBar` power-up
+    pub catch: bool,
 }
 
 impl Config {
     /// Create a new config
+    ///
+    /// The player starts with 3 lives and the ball leaves the bar at up to 60 degrees from
+    /// vertical. The ball starts moving one unit each tick and speeds up to two units each tick as
+    /// blocks are hit and time passes. One in ten destroyed blocks drop a power-up, each kind as
+    /// likely as the others, with effects lasting 600 ticks. The game runs at 20 ticks a second. The bar moves at up to one unit each
+    /// tick and doesn't spin the ball or catch it. These can be changed through the public fields.
     pub fn new(block_density: f64, block_strength: u16, bar_width: u16, ball_power: u16) -> Self {
         Self {
             block_density,
             block_strength,
             bar_width,
             ball_power,
+            lives: 3,
+            max_bounce_angle: 60.,
+            ball_speed: 1.,
+            max_ball_speed: 2.,
+            ball_acceleration: 0.02,
+            speed_up_interval: 600,
+            power_up_chance: 0.1,
+            power_up_weights: [1.; 6],
+            power_up_fall_speed: 0.25,
+            power_up_duration: 600,
+            ticks_per_second: 20,
+            bar_max_speed: 1.,
+            bar_acceleration: 0.25,
+            bar_friction: 0.2,
+            bar_spin: 0.,
+            catch: false,
+        }
+    }
+
+    /// Create a config with the settings for a difficulty
+    pub fn preset(difficulty: Difficulty) -> Self {
+        match difficulty {
+            Difficulty::Easy => Self {
+                lives: 5,
+                ball_speed: 0.7,
+                max_ball_speed: 1.2,
+                ball_acceleration: 0.01,
+                speed_up_interval: 900,
+                power_up_chance: 0.2,
+                power_up_fall_speed: 0.2,
+                power_up_duration: 900,
+                catch: true,
+                ..Self::new(0.3, 1, 13, 1)
+            },
+            Difficulty::Normal => Self::new(0.5, 1, 9, 1),
+            Difficulty::Hard => Self {
+                max_bounce_angle: 65.,
+                ball_speed: 1.2,
+                max_ball_speed: 2.5,
+                ball_acceleration: 0.03,
+                speed_up_interval: 450,
+                power_up_chance: 0.08,
+                power_up_fall_speed: 0.3,
+                power_up_duration: 450,
+                ..Self::new(0.6, 2, 7, 1)
+            },
+            Difficulty::Insane => Self {
+                lives: 1,
+                max_bounce_angle: 70.,
+                ball_speed: 1.5,
+                max_ball_speed: 3.,
+                ball_acceleration: 0.05,
+                speed_up_interval: 300,
+                power_up_chance: 0.05,
+                power_up_fall_speed: 0.4,
+                power_up_duration: 300,
+                ..Self::new(0.8, 3, 5, 1)
+            },
+        }
+    }
+
+    /// Start building a config from the `Normal` difficulty
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::new(Difficulty::Normal)
+    }
+
+    /// Create a new config, checking it can be played with
+    pub fn try_new(
+        block_density: f64,
+        block_strength: u16,
+        bar_width: u16,
+        ball_power: u16,
+    ) -> Result<Self, Error> {
+        let config = Self::new(block_density, block_strength, bar_width, ball_power);
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check the config can be played with
+    ///
+    /// Probabilities must be between 0 and 1, the bar, blocks and ball must have a size or
+    /// strength of at least 1 and the ball must move.
+    /// There must be at least one tick a second.
+    pub fn validate(&self) -> Result<(), Error> {
+        let invalid = |field, expected| Err(Error::InvalidConfig { field, expected });
+        let probability = |value: f64| (0. ..=1.).contains(&value);
+        let positive = |value: f64| value > 0. && value.is_finite();
+        if !probability(self.block_density) {
+            return invalid("block_density", "between 0 and 1");
+        }
+        if self.block_strength == 0 {
+            return invalid("block_strength", "at least 1");
+        }
+        if self.bar_width == 0 {
+            return invalid("bar_width", "at least 1");
+        }
+        if self.ball_power == 0 {
+            return invalid("ball_power", "at least 1");
+        }
+        if self.lives == 0 {
+            return invalid("lives", "at least 1");
+        }
+        if !(0. ..90.).contains(&self.max_bounce_angle) {
+            return invalid("max_bounce_angle", "at least 0 and less than 90");
+        }
+        if !positive(self.ball_speed) {
+            return invalid("ball_speed", "positive");
+        }
+        if !(self.max_ball_speed >= self.ball_speed && self.max_ball_speed.is_finite()) {
+            return invalid("max_ball_speed", "at least ball_speed");
+        }
+        if !(self.ball_acceleration >= 0. && self.ball_acceleration.is_finite()) {
+            return invalid("ball_acceleration", "at least 0");
+        }
+        if !probability(self.power_up_chance) {
+            return invalid("power_up_chance", "between 0 and 1");
+        }
+        let weights = &self.power_up_weights;
+        let weight = |weight: f64| weight >= 0. && weight.is_finite();
+        if !weights.iter().all(|&w| weight(w)) || weights.iter().sum::<f64>() <= 0. {
+            return invalid("power_up_weights", "at least 0, with at least one above 0");
+        }
+        if !positive(self.power_up_fall_speed) {
+            return invalid("power_up_fall_speed", "positive");
+        }
+        if self.ticks_per_second == 0 {
+            return invalid("ticks_per_second", "at least 1");
+        }
+        if !positive(self.bar_max_speed) {
+            return invalid("bar_max_speed", "positive");
+        }
+        if !positive(self.bar_acceleration) {
+            return invalid("bar_acceleration", "positive");
+        }
+        if !probability(self.bar_friction) {
+            return invalid("bar_friction", "between 0 and 1");
+        }
+        if !self.bar_spin.is_finite() {
+            return invalid("bar_spin", "finite");
+        }
+        Ok(())
+    }
+
+    /// Get a copy of the config with each invalid value replaced by the nearest valid one
+    ///
+    /// Values that aren't numbers, or have no nearest valid value such as an infinite or
+    /// non-positive speed, are replaced by the value from `Config::default`. This is used by the
+    /// constructors of `GameState` that don't return errors.
+    pub fn clamped(&self) -> Config {
+        let default = Config::default();
+        let number = |value: f64, default: f64| if value.is_nan() { default } else { value };
+        let finite = |value: f64, default: f64| if value.is_finite() { value } else { default };
+        let positive = |value: f64, default: f64| {
+            if value > 0. && value.is_finite() {
+                value
+            } else {
+                default
+            }
+        };
+        let ball_speed = positive(self.ball_speed, default.ball_speed);
+        Config {
+            block_density: number(self.block_density, default.block_density).clamp(0., 1.),
+            block_strength: self.block_strength.max(1),
+            bar_width: self.bar_width.max(1),
+            ball_power: self.ball_power.max(1),
+            lives: self.lives.max(1),
+            max_bounce_angle: number(self.max_bounce_angle, default.max_bounce_angle)
+                .clamp(0., MAX_BOUNCE_ANGLE),
+            ball_speed,
+            max_ball_speed: finite(self.max_ball_speed, default.max_ball_speed).max(ball_speed),
+            ball_acceleration: finite(self.ball_acceleration, default.ball_acceleration).max(0.),
+            power_up_chance: number(self.power_up_chance, default.power_up_chance).clamp(0., 1.),
+            power_up_weights: clamp_weights(&self.power_up_weights, &default.power_up_weights),
+            power_up_fall_speed: positive(self.power_up_fall_speed, default.power_up_fall_speed),
+            ticks_per_second: self.ticks_per_second.max(1),
+            bar_max_speed: positive(self.bar_max_speed, default.bar_max_speed),
+            bar_acceleration: positive(self.bar_acceleration, default.bar_acceleration),
+            bar_friction: number(self.bar_friction, default.bar_friction).clamp(0., 1.),
+            bar_spin: finite(self.bar_spin, default.bar_spin),
+            ..self.clone()
+        }
+    }
+
+    /// Get the density to generate blocks at for a level
+    ///
+    /// The density increases with each level, up to every position holding a block.
+    pub fn level_block_density(&self, level: u16) -> f64 {
+        let increase = f64::from(level.saturating_sub(1)) * LEVEL_DENSITY_STEP;
+        (self.block_density + increase).min(1.)
+    }
+
+    /// Get the strength of each block for a level
+    ///
+    /// The strength increases by one every couple of levels.
+    pub fn level_block_strength(&self, level: u16) -> u16 {
+        self.block_strength
+            .saturating_add(level.saturating_sub(1) / LEVELS_PER_STRENGTH)
+    }
+}
+
+/// The default config is the `Normal` difficulty
+impl Default for Config {
+    fn default() -> Self {
+        Self::preset(Difficulty::Normal)
+    }
+}
+
+/// Replace each weight that isn't a number or is infinite with its default and negative weights
+/// with 0, using the defaults for all of them if they are all 0
+fn clamp_weights(weights: &[f64; 6], defaults: &[f64; 6]) -> [f64; 6] {
+    let mut clamped = *defaults;
+    for (weight, &value) in clamped.iter_mut().zip(weights) {
+        if value.is_finite() {
+            *weight = value.max(0.)
+        }
+    }
+    if clamped.iter().sum::<f64>() > 0. {
+        clamped
+    } else {
+        *defaults
+    }
+}
+
+/// A named set of settings for the game
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Difficulty {
+    /// Fewer, weaker blocks, a wide bar that catches the ball, a slow ball and more lives
+    Easy,
+    /// The settings from `Config::new(0.5, 1, 9, 1)`
+    #[default]
+    Normal,
+    /// More, stronger blocks, a narrow bar and a fast ball
+    Hard,
+    /// Dense, strong blocks, a tiny bar, a very fast ball and a single life
+    Insane,
+}
+
+impl Difficulty {
+    /// All of the difficulties, from easiest to hardest
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Insane,
+    ];
+
+    /// Get the lowercase name of the difficulty
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Insane => "insane",
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = Error;
+
+    /// Parse the name of a difficulty, ignoring case
+    fn from_str(name: &str) -> Result<Difficulty, Error> {
+        Difficulty::ALL
+            .iter()
+            .cloned()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| Error::UnknownDifficulty(name.to_string()))
+    }
+}
+
+/// Builds a config, starting from the settings for a difficulty
+///
+/// ```
+/// use block_breaker::{Config, Difficulty};
+///
+/// let config = Config::builder().difficulty(Difficulty::Hard).lives(5).build().unwrap();
+/// assert_eq!(config.lives, 5);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    /// Start building a config from the settings for a difficulty
+    pub fn new(difficulty: Difficulty) -> ConfigBuilder {
+        ConfigBuilder {
+            config: Config::preset(difficulty),
         }
     }
+
+    /// Replace all of the settings with those for a difficulty
+    pub fn difficulty(mut self, difficulty: Difficulty) -> ConfigBuilder {
+        self.config = Config::preset(difficulty);
+        self
+    }
+
+    /// Set the density to generate blocks at
+    pub fn block_density(mut self, block_density: f64) -> ConfigBuilder {
+        self.config.block_density = block_density;
+        self
+    }
+
+    /// Set the strength of each block
+    pub fn block_strength(mut self, block_strength: u16) -> ConfigBuilder {
+        self.config.block_strength = block_strength;
+        self
+    }
+
+    /// Set the width of the bar
+    pub fn bar_width(mut self, bar_width: u16) -> ConfigBuilder {
+        self.config.bar_width = bar_width;
+        self
+    }
+
+    /// Set the strength of the ball
+    pub fn ball_power(mut self, ball_power: u16) -> ConfigBuilder {
+        self.config.ball_power = ball_power;
+        self
+    }
+
+    /// Set the number of lives the player starts with
+    pub fn lives(mut self, lives: u16) -> ConfigBuilder {
+        self.config.lives = lives;
+        self
+    }
+
+    /// Set the largest angle from vertical, in degrees, that the ball leaves the bar at
+    pub fn max_bounce_angle(mut self, max_bounce_angle: f64) -> ConfigBuilder {
+        self.config.max_bounce_angle = max_bounce_angle;
+        self
+    }
+
+    /// Set the distance the ball travels each tick when it is served
+    pub fn ball_speed(mut self, ball_speed: f64) -> ConfigBuilder {
+        self.config.ball_speed = ball_speed;
+        self
+    }
+
+    /// Set the fastest the ball can travel
+    pub fn max_ball_speed(mut self, max_ball_speed: f64) -> ConfigBuilder {
+        self.config.max_ball_speed = max_ball_speed;
+        self
+    }
+
+    /// Set how much the ball speeds up by
+    pub fn ball_acceleration(mut self, ball_acceleration: f64) -> ConfigBuilder {
+        self.config.ball_acceleration = ball_acceleration;
+        self
+    }
+
+    /// Set the number of ticks between the ball speeding up
+    pub fn speed_up_interval(mut self, speed_up_interval: u32) -> ConfigBuilder {
+        self.config.speed_up_interval = speed_up_interval;
+        self
+    }
+
+    /// Set the probability of a destroyed block dropping a power-up
+    pub fn power_up_chance(mut self, power_up_chance: f64) -> ConfigBuilder {
+        self.config.power_up_chance = power_up_chance;
+        self
+    }
+
+    /// Set how likely each kind of power-up is to be dropped
+    pub fn power_up_weights(mut self, power_up_weights: [f64; 6]) -> ConfigBuilder {
+        self.config.power_up_weights = power_up_weights;
+        self
+    }
+
+    /// Set the distance a power-up falls each tick
+    pub fn power_up_fall_speed(mut self, power_up_fall_speed: f64) -> ConfigBuilder {
+        self.config.power_up_fall_speed = power_up_fall_speed;
+        self
+    }
+
+    /// Set the number of ticks the effects of timed power-ups last for
+    pub fn power_up_duration(mut self, power_up_duration: u32) -> ConfigBuilder {
+        self.config.power_up_duration = power_up_duration;
+        self
+    }
+
+    /// Set the number of ticks in each second of game time
+    pub fn ticks_per_second(mut self, ticks_per_second: u32) -> ConfigBuilder {
+        self.config.ticks_per_second = ticks_per_second;
+        self
+    }
+
+    /// Set the furthest the bar moves each tick
+    pub fn bar_max_speed(mut self, bar_max_speed: f64) -> ConfigBuilder {
+        self.config.bar_max_speed = bar_max_speed;
+        self
+    }
+
+    /// Set how much the bar speeds up each tick at full input
+    pub fn bar_acceleration(mut self, bar_acceleration: f64) -> ConfigBuilder {
+        self.config.bar_acceleration = bar_acceleration;
+        self
+    }
+
+    /// Set the fraction of the bar's speed lost each tick
+    pub fn bar_friction(mut self, bar_friction: f64) -> ConfigBuilder {
+        self.config.bar_friction = bar_friction;
+        self
+    }
+
+    /// Set the degrees the ball is turned for each unit the bar moved in the last tick
+    pub fn bar_spin(mut self, bar_spin: f64) -> ConfigBuilder {
+        self.config.bar_spin = bar_spin;
+        self
+    }
+
+    /// Set whether balls hitting the top of the bar stick to it until they are launched
+    pub fn catch(mut self, catch: bool) -> ConfigBuilder {
+        self.config.catch = catch;
+        self
+    }
+
+    /// Finish building the config, checking it can be played with
+    pub fn build(self) -> Result<Config, Error> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod file {
+    use super::{Config, Difficulty};
+    use crate::error::Error;
+    use serde_json::Value;
+
+    /// The key in a config file naming the difficulty to take missing settings from
+    const DIFFICULTY_KEY: &str = "difficulty";
+
+    impl Config {
+        /// Load a config from a JSON document
+        ///
+        /// The document can name a `difficulty` to take settings it doesn't give from, otherwise
+        /// they are taken from the `Normal` difficulty. The config is validated after loading.
+        ///
+        /// ```
+        /// use block_breaker::Config;
+        ///
+        /// let config = Config::from_json(r#"{ "difficulty": "hard", "lives": 5 }"#).unwrap();
+        /// assert_eq!(config.lives, 5);
+        /// ```
+        pub fn from_json(text: &str) -> Result<Config, Error> {
+            let value = serde_json::from_str(text).map_err(config_file_error)?;
+            Config::from_value(value)
+        }
+
+        /// Load a config from a TOML document, in the same way as `from_json`
+        ///
+        /// ```
+        /// use block_breaker::Config;
+        ///
+        /// let config = Config::from_toml("difficulty = \"easy\"\nbar_width = 11").unwrap();
+        /// assert_eq!(config.bar_width, 11);
+        /// ```
+        #[cfg(feature = "toml")]
+        pub fn from_toml(text: &str) -> Result<Config, Error> {
+            let value = toml::from_str(text).map_err(config_file_error)?;
+            Config::from_value(value)
+        }
+
+        fn from_value(value: Value) -> Result<Config, Error> {
+            let mut settings = match value {
+                Value::Object(settings) => settings,
+                _ => {
+                    return Err(Error::ConfigFile(
+                        "expected a table of settings".to_string(),
+                    ))
+                }
+            };
+            let difficulty = match settings.remove(DIFFICULTY_KEY) {
+                Some(Value::String(name)) => name.parse()?,
+                Some(_) => {
+                    return Err(Error::ConfigFile(
+                        "difficulty should be the name of a difficulty".to_string(),
+                    ))
+                }
+                None => Difficulty::Normal,
+            };
+            let mut config =
+                serde_json::to_value(Config::preset(difficulty)).map_err(config_file_error)?;
+            if let Value::Object(fields) = &mut config {
+                fields.extend(settings)
+            }
+            let config: Config = serde_json::from_value(config).map_err(config_file_error)?;
+            config.validate()?;
+            Ok(config)
+        }
+    }
+
+    fn config_file_error(error: impl std::fmt::Display) -> Error {
+        Error::ConfigFile(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_config() {
+        assert!(Config::try_new(0.5, 1, 9, 1).is_ok());
+        let invalid = |config: Config| match config.validate() {
+            Err(Error::InvalidConfig { field, .. }) => field,
+            _ => "",
+        };
+        assert_eq!(invalid(Config::new(-0.1, 1, 9, 1)), "block_density");
+        assert_eq!(invalid(Config::new(0.5, 1, 0, 1)), "bar_width");
+        let mut config = Config::new(0.5, 1, 9, 1);
+        config.max_ball_speed = 0.5;
+        assert_eq!(invalid(config.clone()), "max_ball_speed");
+        config.power_up_chance = f64::NAN;
+        config.max_ball_speed = 2.;
+        assert_eq!(invalid(config.clone()), "power_up_chance");
+        config.power_up_chance = 0.1;
+        config.power_up_weights = [0.; 6];
+        assert_eq!(invalid(config.clone()), "power_up_weights");
+        config.power_up_weights[2] = f64::INFINITY;
+        assert_eq!(invalid(config.clone()), "power_up_weights");
+        config.power_up_weights = [0., 0., 1., 0., 0., 0.];
+        config.bar_friction = 1.5;
+        assert_eq!(invalid(config), "bar_friction");
+    }
+
+    #[test]
+    fn clamped_configs_are_valid() {
+        let mut config = Config::new(1.5, 0, 0, 0);
+        config.lives = 0;
+        config.max_bounce_angle = 90.;
+        config.ball_speed = -1.;
+        config.max_ball_speed = f64::INFINITY;
+        config.ball_acceleration = f64::NAN;
+        config.power_up_chance = f64::NAN;
+        config.bar_friction = -0.5;
+        config.bar_spin = f64::NAN;
+        config.power_up_weights = [-1., f64::NAN, 2., 0., 0., 0.];
+        let clamped = config.clamped();
+        clamped.validate().unwrap();
+        let default = Config::default();
+        assert_eq!(clamped.block_density, 1.);
+        assert_eq!(clamped.lives, 1);
+        assert_eq!(clamped.max_bounce_angle, MAX_BOUNCE_ANGLE);
+        assert_eq!(clamped.ball_speed, default.ball_speed);
+        assert_eq!(clamped.max_ball_speed, default.max_ball_speed);
+        assert_eq!(clamped.ball_acceleration, default.ball_acceleration);
+        assert_eq!(clamped.power_up_chance, default.power_up_chance);
+        assert_eq!(clamped.bar_friction, 0.);
+        assert_eq!(clamped.power_up_weights, [0., 1., 2., 0., 0., 0.]);
+        config.power_up_weights = [0.; 6];
+        assert_eq!(config.clamped().power_up_weights, default.power_up_weights);
+
+        let config = Config::preset(Difficulty::Hard);
+        assert_eq!(config.clamped(), config);
+    }
+
+    #[test]
+    fn presets_are_valid() {
+        for &difficulty in &Difficulty::ALL {
+            Config::preset(difficulty).validate().unwrap();
+            assert_eq!(difficulty.name().parse::<Difficulty>(), Ok(difficulty));
+        }
+        assert_eq!(Config::default(), Config::new(0.5, 1, 9, 1));
+        assert_eq!("HARD".parse::<Difficulty>(), Ok(Difficulty::Hard));
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
+
+    #[test]
+    fn builder() {
+        let config = Config::builder()
+            .difficulty(Difficulty::Easy)
+            .lives(9)
+            .bar_width(3)
+            .build()
+            .unwrap();
+        assert_eq!(config.lives, 9);
+        assert_eq!(config.bar_width, 3);
+        assert_eq!(
+            config.ball_speed,
+            Config::preset(Difficulty::Easy).ball_speed
+        );
+        assert!(config.catch);
+        assert!(!Config::builder().build().unwrap().catch);
+        assert!(Config::builder().power_up_chance(2.).build().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn load_json() {
+        assert_eq!(Config::from_json("{}"), Ok(Config::default()));
+        let config = Config::from_json(r#"{"difficulty": "insane", "lives": 2}"#).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                lives: 2,
+                ..Config::preset(Difficulty::Insane)
+            }
+        );
+        assert!(matches!(
+            Config::from_json(r#"{"live": 2}"#),
+            Err(Error::ConfigFile(_))
+        ));
+        assert!(matches!(
+            Config::from_json(r#"{"block_density": 2}"#),
+            Err(Error::InvalidConfig { .. })
+        ));
+        assert_eq!(
+            Config::from_json(r#"{"difficulty": "silly"}"#),
+            Err(Error::UnknownDifficulty("silly".to_string()))
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn load_toml() {
+        let config = Config::from_toml("difficulty = \"hard\"\nblock_density = 1\n").unwrap();
+        assert_eq!(
+            config,
+            Config {
+                block_density: 1.,
+                ..Config::preset(Difficulty::Hard)
+            }
+        );
+    }
 }
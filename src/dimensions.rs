@@ -1,8 +1,11 @@
+use crate::checksum::{Checksum, Fnv1a};
+
 type Height = u16;
 type Width = u16;
 
 /// Generic dimensions
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Dimensions {
     width: Width,
     height: Height,
@@ -24,3 +27,10 @@ impl Dimensions {
         self.height
     }
 }
+
+impl Checksum for Dimensions {
+    fn checksum(&self, checksum: &mut Fnv1a) {
+        checksum.write_u16(self.width);
+        checksum.write_u16(self.height);
+    }
+}
@@ -0,0 +1,349 @@
+//! A reinforcement learning environment for training agents to play the game
+//!
+//! ```
+//! use block_breaker::env::{Action, Environment};
+//!
+//! let mut environment = Environment::new(40, 20, &block_breaker::Config::default());
+//! environment.reset(1);
+//! let (_observation, _reward, _done, _info) = environment.step(Action::Launch);
+//! ```
+
+use crate::config::Config;
+use crate::error::Error;
+use crate::event::Event;
+use crate::gamestate::GameState;
+use crate::level::{BLOCK_HEIGHT, BLOCK_WIDTH};
+use crate::replay::Input;
+use crate::state::State;
+
+/// An action an agent can take in an `Environment` each step
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Let the bar slow down and stop
+    Stay,
+    /// Drive the bar left at full speed
+    Left,
+    /// Drive the bar right at full speed
+    Right,
+    /// Launch the ball if it is being served or stuck to the bar
+    Launch,
+}
+
+impl Action {
+    /// All of the actions, the index of an action in this is its discrete action number
+    pub const ALL: [Action; 4] = [Action::Stay, Action::Left, Action::Right, Action::Launch];
+
+    /// Get the input to give the game for this action
+    pub fn input(self) -> Input {
+        Input {
+            left: self == Action::Left,
+            right: self == Action::Right,
+            launch: self == Action::Launch,
+            ..Input::default()
+        }
+    }
+}
+
+/// How the reward for a step is calculated
+///
+/// The reward is the sum of each weight multiplied by how often its event happened in the step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rewards {
+    /// The reward for each point scored
+    pub point: f64,
+    /// The reward for each time a ball hits the bar
+    pub bar_hit: f64,
+    /// The reward for each life lost, usually negative
+    pub life_lost: f64,
+    /// The reward for clearing a level
+    pub level_cleared: f64,
+    /// The reward for every step, negative to encourage clearing levels quickly
+    pub step: f64,
+}
+
+impl Default for Rewards {
+    fn default() -> Rewards {
+        Rewards {
+            point: 0.01,
+            bar_hit: 0.,
+            life_lost: -1.,
+            level_cleared: 10.,
+            step: 0.,
+        }
+    }
+}
+
+/// What an agent observes of the game after each step
+///
+/// Positions are scaled to between 0 and 1 by the game dimensions. The ball fields are for the
+/// first ball in play and are all 0 when there are no balls.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+    /// The x coordinate of the ball
+    pub ball_x: f64,
+    /// The y coordinate of the ball
+    pub ball_y: f64,
+    /// The distance the ball moves along the x axis each tick
+    pub ball_dx: f64,
+    /// The distance the ball moves along the y axis each tick
+    pub ball_dy: f64,
+    /// The x coordinate of the left end of the bar
+    pub bar_x: f64,
+    /// Whether each block sized cell of the game has a block in it, row by row
+    pub blocks: Vec<bool>,
+}
+
+impl Observation {
+    fn new(game: &GameState) -> Observation {
+        let (width, height) = (f64::from(game.width()), f64::from(game.height()));
+        let (columns, rows) = grid_size(game.width(), game.height());
+        let mut blocks = vec![false; columns * rows];
+        for block in game.blocks() {
+            for x in block.x()..block.x() + block.width() {
+                for y in block.y()..block.y() + block.height() {
+                    let (column, row) =
+                        (usize::from(x / BLOCK_WIDTH), usize::from(y / BLOCK_HEIGHT));
+                    if column < columns && row < rows {
+                        blocks[row * columns + column] = true
+                    }
+                }
+            }
+        }
+        let (ball_x, ball_y, ball_dx, ball_dy) = match game.balls().first() {
+            Some(ball) => {
+                let position = ball.position();
+                let velocity = ball.velocity();
+                (
+                    position.exact_x() / width,
+                    position.exact_y() / height,
+                    velocity.x() * ball.speed(),
+                    velocity.y() * ball.speed(),
+                )
+            }
+            None => (0., 0., 0., 0.),
+        };
+        Observation {
+            ball_x,
+            ball_y,
+            ball_dx,
+            ball_dy,
+            bar_x: game.bar().exact_x() / width,
+            blocks,
+        }
+    }
+
+    /// Flatten the observation into a vector of `Environment::observation_size` values
+    ///
+    /// The ball and bar fields come first in the order they are declared, then the blocks as 1
+    /// where there is a block and 0 where there isn't.
+    pub fn to_vec(&self) -> Vec<f64> {
+        let mut values = vec![
+            self.ball_x,
+            self.ball_y,
+            self.ball_dx,
+            self.ball_dy,
+            self.bar_x,
+        ];
+        values.extend(self.blocks.iter().map(|&block| if block { 1. } else { 0. }));
+        values
+    }
+}
+
+/// Extra information about a step, not meant to be used by the agent
+#[derive(Clone, Debug, PartialEq)]
+pub struct Info {
+    /// The score of the game
+    pub score: u64,
+    /// The lives remaining
+    pub lives: u16,
+    /// The current level
+    pub level: u16,
+    /// The number of steps since the environment was reset
+    pub steps: u32,
+    /// The events from the tick of the step
+    pub events: Vec<Event>,
+}
+
+/// A reinforcement learning environment for the game
+///
+/// Each step applies an action and ticks the game once. Cleared levels are advanced past
+/// automatically, so an episode runs until the game is over or `max_steps` is reached.
+pub struct Environment {
+    width: u16,
+    height: u16,
+    config: Config,
+    game: GameState,
+    steps: u32,
+    /// How the reward for each step is calculated
+    pub rewards: Rewards,
+    /// The most steps in an episode, unlimited if `None`
+    pub max_steps: Option<u32>,
+}
+
+impl Environment {
+    /// Create a new environment for games of the given dimensions and config
+    ///
+    /// The environment starts with a game from seed 0, call `reset` to start an episode.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the game is too small, use `try_new` to get an error instead.
+    pub fn new(width: u16, height: u16, config: &Config) -> Environment {
+        Environment::with_game(
+            width,
+            height,
+            config,
+            GameState::from_seed(width, height, config, 0),
+        )
+    }
+
+    /// Create a new environment, checking the config is valid and the game is big enough
+    pub fn try_new(width: u16, height: u16, config: &Config) -> Result<Environment, Error> {
+        let game = GameState::try_from_seed(width, height, config, 0)?;
+        Ok(Environment::with_game(width, height, config, game))
+    }
+
+    fn with_game(width: u16, height: u16, config: &Config, game: GameState) -> Environment {
+        Environment {
+            width,
+            height,
+            config: config.clone(),
+            game,
+            steps: 0,
+            rewards: Rewards::default(),
+            max_steps: None,
+        }
+    }
+
+    /// Get the number of values in a flattened observation
+    pub fn observation_size(&self) -> usize {
+        let (columns, rows) = grid_size(self.width, self.height);
+        5 + columns * rows
+    }
+
+    /// Retrieve the game being played
+    pub fn game(&self) -> &GameState {
+        &self.game
+    }
+
+    /// Start a new episode with a game from `seed`
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.game = GameState::from_seed(self.width, self.height, &self.config, seed);
+        self.steps = 0;
+        Observation::new(&self.game)
+    }
+
+    /// Take an action and tick the game, returning the observation, reward, whether the episode
+    /// is done and extra information
+    pub fn step(&mut self, action: Action) -> (Observation, f64, bool, Info) {
+        let score = self.game.score();
+        action.input().apply(&mut self.game);
+        self.game.tick();
+        self.steps += 1;
+
+        let events: Vec<_> = self.game.drain_events().collect();
+        let rewards = &self.rewards;
+        let mut reward = rewards.step + (self.game.score() - score) as f64 * rewards.point;
+        for event in &events {
+            reward += match event {
+                Event::BallHitBar { .. } => rewards.bar_hit,
+                Event::LifeLost { .. } => rewards.life_lost,
+                Event::LevelCleared { .. } => rewards.level_cleared,
+                _ => 0.,
+            }
+        }
+        if self.game.state() == State::LevelCleared {
+            self.game.next_level()
+        }
+
+        let done = self.game.game_over() || self.max_steps.is_some_and(|max| self.steps >= max);
+        let info = Info {
+            score: self.game.score(),
+            lives: self.game.lives(),
+            level: self.game.level(),
+            steps: self.steps,
+            events,
+        };
+        (Observation::new(&self.game), reward, done, info)
+    }
+}
+
+/// Get the number of columns and rows of block sized cells in a game
+fn grid_size(width: u16, height: u16) -> (usize, usize) {
+    (
+        usize::from(width.div_ceil(BLOCK_WIDTH)),
+        usize::from(height.div_ceil(BLOCK_HEIGHT)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn environment() -> Environment {
+        let mut config = Config::new(0.5, 1, 9, 1);
+        config.lives = 1;
+        Environment::new(30, 20, &config)
+    }
+
+    #[test]
+    fn reset_is_reproducible() {
+        let mut env = environment();
+        let observation = env.reset(3);
+        assert_eq!(observation.to_vec().len(), env.observation_size());
+        assert!(observation.blocks.contains(&true));
+        assert_eq!(env.reset(3), observation);
+    }
+
+    #[test]
+    fn try_new_checks_the_game() {
+        let config = Config::new(0.5, 1, 9, 1);
+        assert!(matches!(
+            Environment::try_new(8, 20, &config),
+            Err(Error::TooSmall { .. })
+        ));
+        assert!(Environment::try_new(30, 20, &Config::new(2., 1, 9, 1)).is_err());
+        assert!(Environment::try_new(30, 20, &config).is_ok());
+    }
+
+    #[test]
+    fn observations_have_the_exact_bar_position() {
+        let mut env = environment();
+        env.reset(3);
+        let (observation, _, _, _) = env.step(Action::Left);
+        let x = env.game().bar().exact_x();
+        assert!(x.fract() != 0.);
+        assert!((observation.bar_x * 30. - x).abs() < 1e-9);
+    }
+
+    #[test]
+    fn episode_ends_at_game_over() {
+        let mut env = environment();
+        env.reset(3);
+        env.step(Action::Launch);
+        // move the bar out of the way so the ball is lost
+        let mut total = 0.;
+        let mut steps = 0;
+        loop {
+            let (observation, reward, done, info) = env.step(Action::Left);
+            assert_eq!(observation.to_vec().len(), env.observation_size());
+            total += reward;
+            steps += 1;
+            if info.events.contains(&Event::LifeLost { lives: 0 }) {
+                assert!(done);
+                break;
+            }
+            assert!(!done && steps < 1000);
+        }
+        assert!(total < 0.);
+    }
+
+    #[test]
+    fn episode_ends_after_max_steps() {
+        let mut env = environment();
+        env.max_steps = Some(2);
+        env.reset(3);
+        assert!(!env.step(Action::Stay).2);
+        assert!(env.step(Action::Stay).2);
+    }
+}
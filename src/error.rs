@@ -0,0 +1,57 @@
+use std::error;
+use std::fmt;
+
+/// An error from creating a config or game, or resizing a game
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The game is too small to play in
+    TooSmall {
+        /// The width asked for
+        width: u16,
+        /// The height asked for
+        height: u16,
+        /// The narrowest the game can be, as wide as the bar
+        min_width: u16,
+        /// The shortest the game can be
+        min_height: u16,
+    },
+    /// A field of the config has a value the game can't be played with
+    InvalidConfig {
+        /// The name of the field
+        field: &'static str,
+        /// What the value should be
+        expected: &'static str,
+    },
+    /// A config file couldn't be read
+    ConfigFile(String),
+    /// There isn't a difficulty with the name
+    UnknownDifficulty(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::TooSmall {
+                width,
+                height,
+                min_width,
+                min_height,
+            } => write!(
+                f,
+                "game of {}x{} is too small, it must be at least {}x{}",
+                width, height, min_width, min_height
+            ),
+            Error::InvalidConfig { field, expected } => {
+                write!(f, "invalid config: {} must be {}", field, expected)
+            }
+            Error::ConfigFile(message) => write!(f, "invalid config file: {}", message),
+            Error::UnknownDifficulty(name) => write!(
+                f,
+                "unknown difficulty {:?}, expected easy, normal, hard or insane",
+                name
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {}
@@ -0,0 +1,64 @@
+use crate::block::BlockKind;
+use crate::powerup::PowerUpKind;
+
+/// Something that happened during a tick of the game
+///
+/// Events are collected by the game as it ticks and can be drained with
+/// [`GameState::drain_events`](crate::GameState::drain_events), to drive sound, particles or
+/// analytics.
+///
+/// Balls are identified by their index in `GameState::balls` during the tick the event happened
+/// in. Balls lost at the end of that tick are removed, which moves the balls after them down, so
+/// the index is only valid until the tick ends.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event {
+    /// A ball bounced off the bar
+    BallHitBar {
+        /// The index of the ball during the tick it hit the bar in
+        ball: usize,
+        /// How far from the centre of the bar the ball hit, from -1 at the left end to 1 at the
+        /// right end
+        offset: f64,
+    },
+    /// A ball bounced off the left, right or top edge of the game
+    BallHitWall {
+        /// The index of the ball during the tick it hit the wall in
+        ball: usize,
+    },
+    /// A block was damaged but not destroyed
+    BlockDamaged {
+        /// The x coordinate of the block
+        x: u16,
+        /// The y coordinate of the block
+        y: u16,
+        /// The kind of the block
+        kind: BlockKind,
+        /// The strength the block has left
+        strength: u16,
+    },
+    /// A block was destroyed
+    BlockDestroyed {
+        /// The x coordinate of the block
+        x: u16,
+        /// The y coordinate of the block
+        y: u16,
+        /// The kind of the block
+        kind: BlockKind,
+    },
+    /// The last ball left the bottom of the game
+    LifeLost {
+        /// The number of lives left
+        lives: u16,
+    },
+    /// Every breakable block in the level has been destroyed
+    LevelCleared {
+        /// The level that was cleared
+        level: u16,
+    },
+    /// The bar caught a power-up
+    PowerUpCollected {
+        /// The kind of power-up caught
+        kind: PowerUpKind,
+    },
+}
@@ -1,72 +1,345 @@
 use crate::ball::Ball;
 use crate::bar::Bar;
-use crate::block::Block;
+use crate::block::{Block, BlockKind};
+use crate::checksum::{Checksum, Fnv1a};
+use crate::collision::{sweep, Aabb, Contact};
 use crate::config::Config;
 use crate::dimensions::Dimensions;
+use crate::error::Error;
+use crate::event::Event;
+use crate::level::{Level, BLOCK_HEIGHT, BLOCK_WIDTH};
 use crate::position::Position;
-use crate::rand::Rng;
+use crate::powerup::{Effect, PowerUp, PowerUpKind, SLOW_BALL_FACTOR, WIDE_BAR_FACTOR};
+use crate::rand::{Rng, SeedableRng};
+use crate::rand_pcg::Pcg32;
+use crate::resize::ResizePolicy;
+use crate::score::Score;
+use crate::state::State;
 use crate::vector::Vector;
+use std::time::Duration;
+
+/// The most times the ball can bounce in a single tick
+const MAX_CONTACTS_PER_TICK: usize = 8;
+/// How far from a contact the ball is placed, so it doesn't end up inside what it hit
+const CONTACT_OFFSET: f64 = 1e-6;
+/// The most ticks a single call to `advance` runs, so a long pause doesn't stall the game while
+/// it catches up
+const MAX_TICKS_PER_ADVANCE: u32 = 10;
+/// The rows at the bottom of the game kept clear of blocks, one each for the ball being served,
+/// the bar and below the bar
+const BAR_ROWS: u16 = 3;
+/// The shortest the game can be, with the blocks generated in the top 7/10 of the game clear of
+/// the bar rows
+const MIN_HEIGHT: u16 = min_height();
+
+const fn min_height() -> u16 {
+    let mut height = BAR_ROWS;
+    while height * 7 / 10 - 1 + BLOCK_HEIGHT + BAR_ROWS > height {
+        height += 1
+    }
+    height
+}
+
+/// Something the ball can hit
+enum Obstacle {
+    Wall,
+    Bar,
+    Block(usize),
+}
 
 /// Struct to store the game items
 ///
-/// Stored items include: ball, bar and blocks as well as game dimensions and paused
-/// state
+/// Stored items include: balls, bar, blocks, falling power-ups and active effects as well as game
+/// dimensions, the dimensions the blocks were laid out in, how to resize the game, the config,
+/// the seed and random number generator, current level, remaining lives, score, the lifecycle
+/// state, the events that haven't been drained yet and the time that hasn't been ticked through
+/// yet
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameState {
-    ball: Ball,
+    balls: Vec<Ball>,
     bar: Bar,
     blocks: Vec<Block>,
+    power_ups: Vec<PowerUp>,
+    effects: Vec<Effect>,
     dimensions: Dimensions,
-    paused: bool,
+    layout: Dimensions,
+    resize_policy: ResizePolicy,
+    config: Config,
+    seed: u64,
+    rng: Pcg32,
+    level: u16,
+    lives: u16,
+    score: Score,
+    play_ticks: u32,
+    state: State,
+    resume_state: State,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    events: Vec<Event>,
+    accumulator: Duration,
+    previous_bar_x: f64,
 }
 
 impl GameState {
     /// Create a new GameState struct.
     /// Uses width and height to set the game dimensions and along with the config generate the blocks in the game.
     /// Generation of the blocks uses some padding around the game_dimensions so blocks aren't too close to the edge.
+    ///
+    /// The seed for the game is picked at random, use `from_seed` to reproduce a game.
+    ///
+    /// Invalid config values are clamped, see `Config::clamped`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the game is too small, use `try_new` to get an error instead, which also
+    /// rejects invalid configs.
     pub fn new(width: u16, height: u16, config: &Config) -> GameState {
-        let block_dimensions = Dimensions::new(3, 1);
-        let mut blocks = Vec::new();
-        for x in ((width / 10)..(width * 9 / 10)).step_by(block_dimensions.width() as usize) {
-            if !(x + block_dimensions.width() <= (width / 2)
-                || x - block_dimensions.width() >= (width / 2))
-            {
-                continue;
-            }
-            for y in ((height / 10)..(height * 7 / 10)).step_by(block_dimensions.height() as usize)
-            {
-                if rand::thread_rng().gen_bool(config.block_density) {
-                    blocks.push(Block::new(
-                        Position::new(x, y),
-                        block_dimensions.clone(),
-                        config.block_strength,
-                    ))
-                }
-            }
-        }
+        GameState::from_rng(width, height, config, &mut rand::thread_rng())
+    }
+
+    /// Create a new GameState struct, checking the config is valid and the game is big enough
+    pub fn try_new(width: u16, height: u16, config: &Config) -> Result<GameState, Error> {
+        GameState::try_from_seed(width, height, config, rand::thread_rng().gen())
+    }
+
+    /// Create a new GameState struct, drawing the seed for the game from `rng`
+    pub fn from_rng<R: Rng>(width: u16, height: u16, config: &Config, rng: &mut R) -> GameState {
+        GameState::from_seed(width, height, config, rng.gen())
+    }
+
+    /// Create a new GameState struct from a seed
+    ///
+    /// The same seed, dimensions and config always generate the same blocks. Invalid config
+    /// values are clamped, see `Config::clamped`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the game is too small, use `try_from_seed` to get an error instead, which also
+    /// rejects invalid configs.
+    pub fn from_seed(width: u16, height: u16, config: &Config, seed: u64) -> GameState {
+        GameState::try_from_seed(width, height, &config.clamped(), seed)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Create a new GameState struct from a seed, checking the config is valid and the game is
+    /// big enough
+    pub fn try_from_seed(
+        width: u16,
+        height: u16,
+        config: &Config,
+        seed: u64,
+    ) -> Result<GameState, Error> {
+        check(width, height, config)?;
+        let mut rng = Pcg32::seed_from_u64(seed);
+        let blocks = generate_blocks(
+            width,
+            height,
+            config.block_density,
+            config.block_strength,
+            &mut rng,
+        );
+        Ok(GameState::with_blocks(
+            width, height, config, seed, rng, blocks,
+        ))
+    }
+
+    /// Create a new GameState struct with the blocks from a hand designed level
+    ///
+    /// The level is only used for the first level of the game, later levels are generated from
+    /// the config. The seed for the game is picked at random, use `from_level_with_seed` to
+    /// reproduce a game. Invalid config values are clamped, see `Config::clamped`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the game is too small, use `try_from_level` to get an error instead, which also
+    /// rejects invalid configs.
+    pub fn from_level(width: u16, height: u16, config: &Config, level: &Level) -> GameState {
+        GameState::try_from_level(width, height, &config.clamped(), level)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Create a new GameState struct with the blocks from a hand designed level, checking the
+    /// config is valid and the game is big enough
+    pub fn try_from_level(
+        width: u16,
+        height: u16,
+        config: &Config,
+        level: &Level,
+    ) -> Result<GameState, Error> {
+        GameState::try_from_level_with_seed(width, height, config, level, rand::thread_rng().gen())
+    }
+
+    /// Create a new GameState struct with the blocks from a hand designed level and a seed
+    ///
+    /// The same seed, level, dimensions and config always play out the same way. Invalid config
+    /// values are clamped, see `Config::clamped`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the game is too small, use `try_from_level_with_seed` to get an error instead,
+    /// which also rejects invalid configs.
+    pub fn from_level_with_seed(
+        width: u16,
+        height: u16,
+        config: &Config,
+        level: &Level,
+        seed: u64,
+    ) -> GameState {
+        GameState::try_from_level_with_seed(width, height, &config.clamped(), level, seed)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Create a new GameState struct with the blocks from a hand designed level and a seed,
+    /// checking the config is valid and the game is big enough
+    pub fn try_from_level_with_seed(
+        width: u16,
+        height: u16,
+        config: &Config,
+        level: &Level,
+        seed: u64,
+    ) -> Result<GameState, Error> {
+        check(width, height, config)?;
+        let rng = Pcg32::seed_from_u64(seed);
+        let blocks = level.blocks(width, height);
+        Ok(GameState::with_blocks(
+            width, height, config, seed, rng, blocks,
+        ))
+    }
+
+    fn with_blocks(
+        width: u16,
+        height: u16,
+        config: &Config,
+        seed: u64,
+        rng: Pcg32,
+        blocks: Vec<Block>,
+    ) -> GameState {
         let game_dimensions = Dimensions::new(width, height);
-        GameState {
-            ball: Ball::new(
-                Position::new(width / 2, height / 2),
-                game_dimensions.clone(),
-                config.ball_power,
-            ),
+        let mut game_state = GameState {
+            balls: Vec::new(),
             bar: Bar::new(
                 Position::new((width / 2) - (config.bar_width / 2), height / 2),
                 Dimensions::new(config.bar_width, 1),
                 game_dimensions.clone(),
             ),
             blocks,
+            power_ups: Vec::new(),
+            effects: Vec::new(),
+            layout: game_dimensions.clone(),
+            resize_policy: ResizePolicy::default(),
             dimensions: game_dimensions,
-            paused: true,
-        }
+            config: config.clone(),
+            seed,
+            rng,
+            level: 1,
+            lives: config.lives,
+            score: Score::new(),
+            play_ticks: 0,
+            state: State::Serving,
+            resume_state: State::Serving,
+            events: Vec::new(),
+            accumulator: Duration::default(),
+            previous_bar_x: 0.,
+        };
+        game_state.previous_bar_x = game_state.bar.exact_x();
+        game_state.serve();
+        game_state
     }
 
-    /// Updates the dimensions of the game, triggering updates to the bar and ball too
+    /// Updates the dimensions of the game, triggering updates to the bar and balls too
+    ///
+    /// # Panics
+    ///
+    /// Panics if the game would be too small, use `try_update_dimensions` to get an error
+    /// instead.
     pub fn update_dimensions(&mut self, width: u16, height: u16) {
+        self.try_update_dimensions(width, height)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Updates the dimensions of the game, leaving the game as it was if it would be too small
+    ///
+    /// The blocks, bar, balls and power-ups are moved according to the resize policy. Balls above
+    /// the bar are kept above it.
+    pub fn try_update_dimensions(&mut self, width: u16, height: u16) -> Result<(), Error> {
+        check_size(width, height, &self.config)?;
+        let old_dimensions = self.dimensions.clone();
         let new_dimensions = Dimensions::new(width, height);
+        let (policy, layout) = (self.resize_policy, &self.layout);
+        let move_point = |x, y| policy.move_point(x, y, layout, &old_dimensions, &new_dimensions);
+
+        let old_bar_y = f64::from(self.bar.y());
+        let half_width = f64::from(self.bar.width()) / 2.;
+        let (centre, _) = move_point(self.bar.exact_x() + half_width, 0.);
         self.bar.update_dimensions(new_dimensions.clone());
-        self.ball.update_dimensions(new_dimensions.clone());
+        self.bar.set_exact_x(centre - half_width);
+        // the bar is moved rather than moving itself, so it isn't interpolated from where it was
+        self.previous_bar_x = self.bar.exact_x();
+
+        let bar_y = f64::from(self.bar.y());
+        for ball in &mut self.balls {
+            let (old_x, old_y) = (ball.position().exact_x(), ball.position().exact_y());
+            let (x, mut y) = move_point(old_x, old_y);
+            if old_y < old_bar_y {
+                y = y.min(bar_y - 0.5)
+            }
+            ball.set_position(Position::from_exact(x.max(0.), y.max(0.)));
+            ball.update_dimensions(new_dimensions.clone());
+        }
+        let max_x = f64::from(width - 1);
+        for power_up in &mut self.power_ups {
+            let position = power_up.position();
+            let (x, y) = move_point(position.exact_x(), position.exact_y());
+            power_up.set_position(Position::from_exact(x.clamp(0., max_x), y.max(0.)))
+        }
+
         self.dimensions = new_dimensions;
+        self.place_blocks();
+        if self.state == State::Serving {
+            self.serve()
+        }
+        Ok(())
+    }
+
+    /// Check the game could have been created by `try_new`, e.g. after it is loaded from a save
+    #[cfg(feature = "serde")]
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        self.config.validate()?;
+        let (width, height) = (self.width(), self.height());
+        check_size(width, height, &self.config)?;
+        if !self.bar.fits(width) {
+            return Err(Error::TooSmall {
+                width,
+                height,
+                min_width: self.bar.width(),
+                min_height: MIN_HEIGHT,
+            });
+        }
+        Ok(())
+    }
+
+    /// Retrieve how the game is changed when it is resized
+    pub fn resize_policy(&self) -> ResizePolicy {
+        self.resize_policy
+    }
+
+    /// Change how the game is changed when it is resized, moving the blocks to where the new
+    /// policy puts them
+    pub fn set_resize_policy(&mut self, policy: ResizePolicy) {
+        self.resize_policy = policy;
+        self.place_blocks()
+    }
+
+    /// Move the blocks to where the resize policy puts them in the current dimensions
+    fn place_blocks(&mut self) {
+        for block in &mut self.blocks {
+            let position = self.resize_policy.place_block(
+                block.origin(),
+                block.dimensions(),
+                &self.layout,
+                &self.dimensions,
+            );
+            block.set_position(position)
+        }
     }
 
     /// Retrieve a mutable reference to the bar
@@ -79,14 +352,48 @@ impl GameState {
         &self.bar
     }
 
-    /// Retrieve a mutable reference to the ball
+    /// Retrieve a mutable reference to the first ball in play
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no balls in play, which happens after the last ball is lost until
+    /// the next one is served.
+    #[deprecated(note = "there can be more than one ball in play, use `balls_mut` instead")]
     pub fn ball_mut(&mut self) -> &mut Ball {
-        &mut self.ball
+        &mut self.balls_mut()[0]
     }
 
-    /// Retrieve an immutable reference to the ball
+    /// Retrieve an immutable reference to the first ball in play
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no balls in play, which happens after the last ball is lost until
+    /// the next one is served.
+    #[deprecated(note = "there can be more than one ball in play, use `balls` instead")]
     pub fn ball(&self) -> &Ball {
-        &self.ball
+        &self.balls[0]
+    }
+
+    /// Retrieve a mutable slice of the balls in play
+    pub fn balls_mut(&mut self) -> &mut [Ball] {
+        &mut self.balls
+    }
+
+    /// Retrieve a slice of the balls in play
+    ///
+    /// There is always at least one ball, apart from after a life is lost or the game is over.
+    pub fn balls(&self) -> &[Ball] {
+        &self.balls
+    }
+
+    /// Add another ball to the game at `position`, travelling in `direction`
+    ///
+    /// The new ball moves at the speed balls are served at.
+    pub fn spawn_ball(&mut self, position: Position, direction: Vector) {
+        let mut ball = Ball::new(position, self.dimensions.clone(), self.config.ball_power);
+        ball.set_velocity(direction);
+        ball.set_speed(self.config.ball_speed);
+        self.balls.push(ball)
     }
 
     /// Retrieve a slice of the blocks
@@ -94,6 +401,41 @@ impl GameState {
         &self.blocks
     }
 
+    /// Retrieve a slice of the power-ups falling towards the bar
+    pub fn power_ups(&self) -> &[PowerUp] {
+        &self.power_ups
+    }
+
+    /// Retrieve a slice of the effects from caught power-ups that are still active
+    pub fn effects(&self) -> &[Effect] {
+        &self.effects
+    }
+
+    /// Retrieve the number of ticks left for the effect of a kind of power-up, if it is active
+    pub fn effect_remaining(&self, kind: PowerUpKind) -> Option<u32> {
+        self.effects
+            .iter()
+            .find(|effect| effect.kind() == kind)
+            .map(Effect::remaining)
+    }
+
+    fn is_active(&self, kind: PowerUpKind) -> bool {
+        self.effect_remaining(kind).is_some()
+    }
+
+    /// Retrieve the events that have happened since they were last drained
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Remove and return the events that have happened since they were last drained
+    ///
+    /// Events build up until they are drained, the game doesn't clear them itself, so whatever
+    /// ticks the game should drain them after each tick.
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, Event> {
+        self.events.drain(..)
+    }
+
     /// Retrieve the game width
     pub fn width(&self) -> u16 {
         self.dimensions.width()
@@ -104,134 +446,1357 @@ impl GameState {
         self.dimensions.height()
     }
 
-    fn collisions(&mut self) {
-        // check ball with bar (simple first)
-        if self.ball().y() + 1 == self.bar().y()
-            && self.bar().x() <= self.ball().x()
-            && self.ball().x() < self.bar().x() + self.bar().width()
-        {
-            // ball collides with bar
-            // Instead of this simplified model the ball should bounce at angles corresponding to how far from the center of the bar it hits. The further the distance, the greater the angle
-            let bar_width = self.bar().width();
-            let bar_midpoint = self.bar().x() + (bar_width / 2);
-            if self.ball().x() == bar_midpoint {
-                self.ball_mut().bounce(Vector::new(0., -1.));
-            } else if self.ball().x() > bar_midpoint {
-                self.ball_mut().bounce(Vector::new(1., -3.));
+    /// Retrieve the current level, starting at 1
+    pub fn level(&self) -> u16 {
+        self.level
+    }
+
+    /// Retrieve the seed the game was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Calculate a checksum of the state of the game
+    ///
+    /// Two games with the same checksum have bit-identical balls, bars, blocks, power-ups,
+    /// scores, lifecycle states and random number generators. The checksum is the same on every
+    /// platform so can be stored and compared later.
+    pub fn checksum(&self) -> u64 {
+        let mut checksum = Fnv1a::default();
+        checksum.write_all(&self.balls);
+        self.bar.checksum(&mut checksum);
+        checksum.write_all(&self.blocks);
+        checksum.write_all(&self.power_ups);
+        checksum.write_all(&self.effects);
+        self.dimensions.checksum(&mut checksum);
+        self.layout.checksum(&mut checksum);
+        checksum.write_u64(self.seed);
+        // the state of the generator is private, so it is checked by the next number it gives
+        checksum.write_u64(self.rng.clone().gen());
+        checksum.write_u16(self.level);
+        checksum.write_u16(self.lives);
+        self.score.checksum(&mut checksum);
+        checksum.write_u32(self.play_ticks);
+        self.state.checksum(&mut checksum);
+        self.resume_state.checksum(&mut checksum);
+        checksum.finish()
+    }
+
+    /// Retrieve the config the game was created with
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Advance to the next level once the current one has been cleared
+    ///
+    /// The blocks are regenerated using the density and strength the config gives for the new
+    /// level and a new ball is served. The score and lives carry over.
+    pub fn next_level(&mut self) {
+        if self.state != State::LevelCleared {
+            return;
+        }
+        self.level += 1;
+        self.blocks = generate_blocks(
+            self.width(),
+            self.height(),
+            self.config.level_block_density(self.level),
+            self.config.level_block_strength(self.level),
+            &mut self.rng,
+        );
+        self.layout = self.dimensions.clone();
+        self.score.reset_combo();
+        self.clear_power_ups();
+        self.serve();
+        self.state = State::Serving
+    }
+
+    /// Retrieve the number of lives remaining
+    pub fn lives(&self) -> u16 {
+        self.lives
+    }
+
+    /// Retrieve the number of points scored
+    pub fn score(&self) -> u64 {
+        self.score.points()
+    }
+
+    /// Retrieve the number of blocks hit since the ball last touched the bar
+    pub fn combo(&self) -> u32 {
+        self.score.combo()
+    }
+
+    /// Retrieve the best combo achieved in this game
+    pub fn best_combo(&self) -> u32 {
+        self.score.best_combo()
+    }
+
+    /// Retrieve the multiplier the current combo applies to points scored
+    pub fn multiplier(&self) -> u32 {
+        self.score.multiplier()
+    }
+
+    /// Retrieve the current lifecycle state of the game
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// Replace the balls with a single ball on top of the middle of the bar
+    fn serve(&mut self) {
+        let x = self.bar.exact_x() + f64::from(self.bar.width()) / 2.;
+        let y = f64::from(self.bar.y()) - 0.5;
+        self.balls.clear();
+        self.spawn_ball(Position::from_exact(x, y.max(0.)), Vector::new(0., 1.));
+        self.play_ticks = 0;
+    }
+
+    /// Launch the ball off the bar if it is being served, or any balls stuck to the bar
+    ///
+    /// Balls leave the bar as if they bounced off it where they sit, so a ball served from the
+    /// middle of a still bar goes straight up and one caught near an end leaves at an angle. A
+    /// moving bar also turns the ball by `Config::bar_spin`, as it does when the ball bounces.
+    pub fn launch(&mut self) {
+        match self.state {
+            State::Serving => {
+                self.serve();
+                self.launch_ball(0);
+                self.state = State::Playing
+            }
+            State::Playing => self.release_balls(),
+            _ => {}
+        }
+    }
+
+    /// Launch any balls stuck to the bar
+    fn release_balls(&mut self) {
+        for ball in 0..self.balls.len() {
+            if self.balls[ball].stuck_offset().is_some() {
+                self.balls[ball].unstick();
+                self.launch_ball(ball)
+            }
+        }
+    }
+
+    /// Send a ball on the bar off at the angle it would bounce off the bar at
+    fn launch_ball(&mut self, ball: usize) {
+        let direction = self.bar.bounce_direction_with_spin(
+            self.balls[ball].exact_x(),
+            self.config.max_bounce_angle,
+            self.config.bar_spin,
+        );
+        self.balls[ball].set_velocity(direction)
+    }
+
+    /// Whether balls hitting the top of the bar stick to it, from `Config::catch` or the
+    /// `StickyBar` power-up
+    pub fn is_catching(&self) -> bool {
+        self.config.catch || self.is_active(PowerUpKind::StickyBar)
+    }
+
+    fn lose_life(&mut self) {
+        self.score.reset_combo();
+        self.clear_power_ups();
+        self.lives = self.lives.saturating_sub(1);
+        self.events.push(Event::LifeLost { lives: self.lives });
+        self.state = if self.lives == 0 {
+            State::GameOver
+        } else {
+            State::LifeLost
+        }
+    }
+
+    /// Damage the block at `index` with a ball, removing it if it is destroyed
+    ///
+    /// Destroyed explosive blocks damage the blocks around them with the same power, which can
+    /// set off further explosions. Destroyed blocks may drop a power-up.
+    fn hit_block(&mut self, index: usize, power: u16) {
+        let mut pending = vec![index];
+        let mut destroyed = Vec::new();
+        while let Some(i) = pending.pop() {
+            let block = &mut self.blocks[i];
+            if !block.kind().is_breakable() {
+                continue;
+            }
+            let strength = block.strength();
+            block.damage(power);
+            let damage = strength - block.strength();
+            let (x, y, kind) = (block.x(), block.y(), block.kind());
+            if !block.is_destroyed() {
+                self.score.hit_block(damage, None);
+                self.events.push(Event::BlockDamaged {
+                    x,
+                    y,
+                    kind,
+                    strength: block.strength(),
+                });
+                continue;
+            }
+            self.score.hit_block(damage, Some(block.initial_strength()));
+            self.events.push(Event::BlockDestroyed { x, y, kind });
+            destroyed.push(i);
+            if let BlockKind::Explosive { radius } = block.kind() {
+                let block = &self.blocks[i];
+                for (j, other) in self.blocks.iter().enumerate() {
+                    if !other.is_destroyed()
+                        && !pending.contains(&j)
+                        && block.is_near(other, radius)
+                    {
+                        pending.push(j)
+                    }
+                }
+            }
+        }
+
+        destroyed.sort_unstable();
+        for i in destroyed.into_iter().rev() {
+            let block = self.blocks.remove(i);
+            let drops = block.kind() == BlockKind::PowerUp
+                || self.rng.gen_bool(self.config.power_up_chance);
+            if drops {
+                let kind = choose_power_up(&self.config.power_up_weights, &mut self.rng);
+                let x = f64::from(block.x()) + f64::from(block.width()) / 2.;
+                let y = f64::from(block.y()) + f64::from(block.height()) / 2.;
+                self.power_ups
+                    .push(PowerUp::new(kind, Position::from_exact(x, y)))
+            }
+        }
+    }
+
+    /// Move the bar by its input or towards its target, see `Bar::set_input` and `Bar::move_to`
+    fn move_bar(&mut self) {
+        self.bar.tick(
+            self.config.bar_acceleration,
+            self.config.bar_friction,
+            self.config.bar_max_speed,
+        )
+    }
+
+    /// Whether every block that can be destroyed has been
+    fn is_level_cleared(&self) -> bool {
+        !self.blocks.iter().any(|block| block.kind().is_breakable())
+    }
+
+    /// Move the falling power-ups down, applying those the bar catches
+    fn move_power_ups(&mut self) {
+        let distance = self.config.power_up_fall_speed;
+        let left = self.bar.exact_x();
+        let right = left + f64::from(self.bar.width());
+        let (top, bottom) = (self.bar.y(), self.bar.y() + self.bar.height());
+        let mut caught = Vec::new();
+        let height = self.height();
+        self.power_ups.retain_mut(|power_up| {
+            let above_bottom = power_up.y() < bottom;
+            power_up.fall(distance);
+            let x = power_up.position().exact_x();
+            if above_bottom && power_up.y() >= top && left <= x && x < right {
+                caught.push(power_up.kind());
+                false
             } else {
-                self.ball_mut().bounce(Vector::new(-1., -3.));
-            }
-        }
-
-        // check ball with top edge of window
-        if self.ball().y() == 0 {
-            self.ball_mut().bounce(Vector::new(0., 1.))
-        }
-
-        // check ball with right edge
-        if self.ball().x() + 1 == self.dimensions.width() {
-            self.ball_mut().bounce(Vector::new(-1., 0.))
-        }
-
-        // check ball with left edge
-        if self.ball().x() == 0 {
-            self.ball_mut().bounce(Vector::new(1., 0.))
-        }
-
-        let mut block_normal_vector = None;
-        let mut block_index = None;
-        // check ball with each block
-        for (i, block) in self.blocks.iter().enumerate() {
-            // ball collided with top edge of block
-            if self.ball().y() == block.y() - 1
-                && block.x() <= self.ball().x()
-                && self.ball.x() < block.x() + block.width()
-            {
-                block_normal_vector = Some(Vector::new(0., -1.));
-                block_index = Some(i);
-            } else if
-            // ball collided with bottom edge of block
-            self.ball().y() == block.y() + block.height()
-                && block.x() <= self.ball().x()
-                && self.ball.x() < block.x() + block.width()
-            {
-                block_normal_vector = Some(Vector::new(0., 1.));
-                block_index = Some(i);
-            } else if
-            // ball collided with right edge of block
-            self.ball().x() == block.x() + block.width()
-                && block.y() <= self.ball().y()
-                && self.ball().y() < block.y() + block.height()
-            {
-                block_normal_vector = Some(Vector::new(1., 0.));
-                block_index = Some(i);
-            } else if
-            // ball collided with left edge of block
-            self.ball().x() + 1 == block.x()
-                && block.y() <= self.ball().y()
-                && self.ball().y() < block.y() + block.height()
-            {
-                block_normal_vector = Some(Vector::new(-1., 0.));
-                block_index = Some(i);
-            }
-
-            if self.ball().x() >= block.x()
-                && self.ball().x() < block.x() + block.width()
-                && self.ball().y() >= block.y()
-                && self.ball().y() < block.y() + block.height()
-            {
-                // ball collides with this block
-                block_normal_vector = Some(Vector::new(1., 1.));
-                block_index = Some(i);
-            }
-        }
-        if let Some(normal) = block_normal_vector {
-            self.ball_mut().bounce(normal)
-        };
-        if let Some(i) = block_index {
-            let power = self.ball().power();
-            self.blocks[i].damage(power);
-            if self.blocks[i].strength() == 0 {
-                self.blocks.remove(i);
+                power_up.y() < height
+            }
+        });
+        for kind in caught {
+            self.events.push(Event::PowerUpCollected { kind });
+            self.apply_power_up(kind)
+        }
+    }
+
+    /// Apply the effect of a caught power-up
+    fn apply_power_up(&mut self, kind: PowerUpKind) {
+        match kind {
+            PowerUpKind::ExtraLife => self.lives = self.lives.saturating_add(1),
+            PowerUpKind::MultiBall => {
+                // copy the highest ball that isn't stuck to the bar, being the furthest from
+                // being lost
+                let source = self
+                    .balls
+                    .iter()
+                    .filter(|ball| ball.stuck_offset().is_none())
+                    .min_by(|a, b| a.exact_y().total_cmp(&b.exact_y()))
+                    .or_else(|| self.balls.first());
+                if let Some(position) = source.map(|ball| ball.position().clone()) {
+                    self.spawn_ball(position.clone(), Vector::new(-1., -1.));
+                    self.spawn_ball(position, Vector::new(1., -1.));
+                }
+            }
+            _ => {}
+        }
+        if !kind.is_timed() {
+            return;
+        }
+        let duration = self.config.power_up_duration;
+        match self.effects.iter_mut().find(|effect| effect.kind() == kind) {
+            Some(effect) => effect.refresh(duration),
+            None => {
+                self.effects.push(Effect::new(kind, duration));
+                if kind == PowerUpKind::WideBar {
+                    let width = f64::from(self.config.bar_width) * WIDE_BAR_FACTOR;
+                    self.bar.set_width(width as u16)
+                }
+            }
+        }
+    }
+
+    /// Count down the active effects, undoing those that run out
+    fn tick_effects(&mut self) {
+        let mut expired = Vec::new();
+        self.effects.retain_mut(|effect| {
+            let done = effect.tick();
+            if done {
+                expired.push(effect.kind())
+            }
+            !done
+        });
+        if expired.contains(&PowerUpKind::WideBar) {
+            self.bar.set_width(self.config.bar_width)
+        }
+        if expired.contains(&PowerUpKind::StickyBar) && !self.is_catching() {
+            // balls caught while the bar was sticky would otherwise be stuck for good
+            self.release_balls()
+        }
+    }
+
+    /// Remove all falling power-ups and active effects
+    fn clear_power_ups(&mut self) {
+        self.power_ups.clear();
+        self.effects.clear();
+        self.bar.set_width(self.config.bar_width)
+    }
+
+    /// Speed a ball up, as it gets harder the longer the ball is in play
+    fn speed_up(&mut self, ball: usize) {
+        let (acceleration, max_speed) = (self.config.ball_acceleration, self.config.max_ball_speed);
+        self.balls[ball].accelerate(acceleration, max_speed)
+    }
+
+    /// Find the earliest thing the ball would hit moving from `(x, y)` by `(dx, dy)`, other than
+    /// the block at `skip`
+    fn earliest_contact(
+        &self,
+        x: f64,
+        y: f64,
+        dx: f64,
+        dy: f64,
+        skip: Option<usize>,
+    ) -> Option<(Contact, Obstacle)> {
+        self.obstacles()
+            .filter(|(_, obstacle)| match *obstacle {
+                Obstacle::Block(i) => Some(i) != skip,
+                _ => true,
+            })
+            .filter_map(|(aabb, obstacle)| {
+                sweep(x, y, dx, dy, &aabb).map(|contact| (contact, obstacle))
+            })
+            .fold(None, |earliest, (contact, obstacle)| match earliest {
+                Some((ref first, _)) if !contact.before(first) => earliest,
+                _ => Some((contact, obstacle)),
+            })
+    }
+
+    /// Get the walls, bar and blocks along with the boxes the balls collide with
+    fn obstacles(&self) -> impl Iterator<Item = (Aabb, Obstacle)> + '_ {
+        let walls = [
+            Aabb::left_of(0.),
+            Aabb::right_of(f64::from(self.width())),
+            Aabb::above(0.),
+        ];
+        let bar = Aabb::new(
+            self.bar.exact_x(),
+            self.bar.exact_y(),
+            self.bar.exact_x() + f64::from(self.bar.width()),
+            self.bar.exact_y() + f64::from(self.bar.height()),
+        );
+        let blocks = self.blocks.iter().enumerate().map(|(i, block)| {
+            let aabb = Aabb::from_cells(block.x(), block.y(), block.width(), block.height());
+            (aabb, Obstacle::Block(i))
+        });
+        IntoIterator::into_iter(walls)
+            .map(|wall| (wall, Obstacle::Wall))
+            .chain(Some((bar, Obstacle::Bar)))
+            .chain(blocks)
+    }
+
+    /// Move a ball that is inside the walls, bar or a block out the shortest way
+    ///
+    /// Balls can end up inside things after a resize, when they are spawned or when the bar moves
+    /// sideways into them. Sweeping only finds the faces a ball moves in through, so a ball that
+    /// starts inside something would otherwise pass straight through it.
+    fn push_out(&mut self, ball: usize) {
+        let position = self.balls[ball].position();
+        let (mut x, mut y) = (position.exact_x(), position.exact_y());
+        let mut moved = false;
+        let piercing = self.is_active(PowerUpKind::PiercingBall);
+        for (aabb, obstacle) in self.obstacles() {
+            if let Obstacle::Block(i) = obstacle {
+                // piercing balls pass through the blocks they hit so can be left inside them
+                if piercing && self.blocks[i].kind().is_breakable() {
+                    continue;
+                }
+            }
+            if let Some((exit_x, exit_y, normal)) = aabb.push_out(x, y) {
+                x = exit_x + normal.x() * CONTACT_OFFSET;
+                y = exit_y + normal.y() * CONTACT_OFFSET;
+                moved = true
+            }
+        }
+        if moved {
+            self.balls[ball].move_to(Position::from_exact(x, y))
+        }
+    }
+
+    /// Move a ball one step along its path, bouncing off anything it hits on the way
+    ///
+    /// The path is swept against the walls, bar and blocks so that the ball can't pass through
+    /// anything, however fast it moves.
+    fn move_ball(&mut self, ball: usize) {
+        if let Some(offset) = self.balls[ball].stuck_offset() {
+            let x = self.bar.exact_x() + offset;
+            let y = f64::from(self.bar.y()) - 0.5;
+            self.balls[ball].move_to(Position::from_exact(x, y.max(0.)));
+            return;
+        }
+        self.push_out(ball);
+        let mut remaining = self.balls[ball].speed();
+        if self.is_active(PowerUpKind::SlowBall) {
+            remaining *= SLOW_BALL_FACTOR
+        }
+        // a block a piercing ball has hit but not destroyed, which it carries on through
+        let mut pierced = None;
+        for _ in 0..MAX_CONTACTS_PER_TICK {
+            let position = self.balls[ball].position();
+            let (x, y) = (position.exact_x(), position.exact_y());
+            let velocity = self.balls[ball].velocity();
+            let (dx, dy) = (velocity.x() * remaining, velocity.y() * remaining);
+            let (contact, obstacle) = match self.earliest_contact(x, y, dx, dy, pierced) {
+                Some(hit) => hit,
+                None => {
+                    self.balls[ball].move_to(Position::from_exact(x + dx, y + dy));
+                    return;
+                }
+            };
+
+            // stop just short of the contact so the ball is never inside what it hit
+            let normal = &contact.normal;
+            let x = x + dx * contact.time + normal.x() * CONTACT_OFFSET;
+            let y = y + dy * contact.time + normal.y() * CONTACT_OFFSET;
+            self.balls[ball].move_to(Position::from_exact(x, y));
+            remaining *= 1. - contact.time;
+
+            match obstacle {
+                Obstacle::Wall => {
+                    self.events.push(Event::BallHitWall { ball });
+                    self.balls[ball].bounce(contact.normal)
+                }
+                Obstacle::Bar => {
+                    self.score.reset_combo();
+                    let offset = self.bar.hit_offset(x);
+                    self.events.push(Event::BallHitBar { ball, offset });
+                    if normal.y() < 0. && self.is_catching() {
+                        self.balls[ball].stick(x - self.bar.exact_x());
+                        return;
+                    } else if normal.y() < 0. {
+                        // the ball leaves at an angle depending on how far from the centre of
+                        // the bar it hits, turned by the bar's movement when spin is on
+                        let direction = self.bar.bounce_direction_with_spin(
+                            x,
+                            self.config.max_bounce_angle,
+                            self.config.bar_spin,
+                        );
+                        self.balls[ball].set_velocity(direction)
+                    } else {
+                        self.balls[ball].bounce(contact.normal)
+                    }
+                }
+                Obstacle::Block(i)
+                    if self.is_active(PowerUpKind::PiercingBall)
+                        && self.blocks[i].kind().is_breakable() =>
+                {
+                    // piercing balls carry on through the blocks they hit, without hitting the
+                    // same block again if it survives, as multi-hit blocks do
+                    let blocks = self.blocks.len();
+                    self.hit_block(i, u16::MAX);
+                    pierced = if self.blocks.len() == blocks {
+                        Some(i)
+                    } else {
+                        None
+                    };
+                    self.speed_up(ball)
+                }
+                Obstacle::Block(i) => {
+                    self.balls[ball].bounce(contact.normal);
+                    let power = self.balls[ball].power();
+                    self.hit_block(i, power);
+                    self.speed_up(ball)
+                }
             }
         }
     }
 
     /// Tick the game state
     ///
-    /// This checks if the game is paused or over and if not causes the game to take a tick. This
-    /// means updating the position of the ball by one step and checking for collisions of the ball
-    /// with the blocks or the bar. If there is a collision then the ball's velocity is updated for
-    /// the next tick to use.
+    /// What a tick does depends on the lifecycle state of the game:
+    ///
+    /// - `Serving`: the bar moves and the ball follows it until it is launched
+    /// - `Playing`: the bar moves, regenerating blocks count down to healing then each ball moves
+    ///   one step, bouncing off the walls, bar and blocks it hits along the way. The balls speed
+    ///   up every `speed_up_interval` ticks. Active effects count down, then power-ups fall and
+    ///   are applied if the bar catches them. Balls reaching the bottom are removed, losing a
+    ///   life once there are none left, and the level is cleared once there are no blocks left
+    ///   other than walls.
+    /// - `LifeLost`: a new ball is served
+    /// - `Paused`, `LevelCleared` and `GameOver`: nothing happens
+    ///
+    /// Hits, lost lives, cleared levels and caught power-ups are recorded as events, see
+    /// `drain_events`.
     pub fn tick(&mut self) {
-        if !self.paused && !self.game_over() {
-            self.ball_mut().tick();
-            self.collisions();
+        self.previous_bar_x = self.bar.exact_x();
+        for ball in &mut self.balls {
+            ball.remember_position()
+        }
+        match self.state {
+            State::Serving => {
+                self.move_bar();
+                self.serve()
+            }
+            State::Playing => {
+                self.move_bar();
+                self.play_ticks += 1;
+                let interval = self.config.speed_up_interval;
+                if interval != 0 && self.play_ticks.is_multiple_of(interval) {
+                    for ball in 0..self.balls.len() {
+                        self.speed_up(ball)
+                    }
+                }
+                for block in &mut self.blocks {
+                    block.tick()
+                }
+                for ball in 0..self.balls.len() {
+                    self.move_ball(ball)
+                }
+                self.tick_effects();
+                self.move_power_ups();
+                let bottom = self.dimensions.height().saturating_sub(1);
+                self.balls.retain(|ball| ball.y() < bottom);
+                if self.balls.is_empty() {
+                    self.lose_life()
+                } else if self.is_level_cleared() {
+                    self.events.push(Event::LevelCleared { level: self.level });
+                    self.state = State::LevelCleared
+                }
+            }
+            State::LifeLost => {
+                self.serve();
+                self.state = State::Serving
+            }
+            State::Paused | State::LevelCleared | State::GameOver => {}
         }
     }
 
+    /// Advance the game by `elapsed` time, running as many ticks as fit in it
+    ///
+    /// Time left over that isn't long enough for another tick is kept for the next call, so the
+    /// game runs at `Config::ticks_per_second` however often this is called. At most 10 ticks
+    /// are run in one call, dropping the rest of the time, so the game doesn't stall catching up
+    /// after a long pause. Returns the number of ticks run.
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        let tick = self.tick_length();
+        self.accumulator += elapsed;
+        let mut ticks = 0;
+        while self.accumulator >= tick {
+            if ticks == MAX_TICKS_PER_ADVANCE {
+                self.accumulator = Duration::default();
+                break;
+            }
+            self.accumulator -= tick;
+            self.tick();
+            ticks += 1;
+        }
+        ticks
+    }
+
+    /// Get how long each tick lasts when advancing the game
+    pub fn tick_length(&self) -> Duration {
+        Duration::from_secs(1) / self.config.ticks_per_second.max(1)
+    }
+
+    /// Get how far through the next tick the game is, from 0 up to 1
+    ///
+    /// Drawing the game this far between where things were at the start of the last tick and
+    /// where they are now makes movement smooth whatever the frame rate.
+    pub fn interpolation(&self) -> f64 {
+        (self.accumulator.as_secs_f64() / self.tick_length().as_secs_f64()).min(1.)
+    }
+
+    /// Get the positions of the balls between the last two ticks, as given by `interpolation`
+    pub fn interpolated_balls(&self) -> Vec<(f64, f64)> {
+        let alpha = self.interpolation();
+        self.balls
+            .iter()
+            .map(|ball| ball.interpolate(alpha))
+            .collect()
+    }
+
+    /// Get the x coordinate of the left end of the bar between the last two ticks, as given by
+    /// `interpolation`
+    pub fn interpolated_bar_x(&self) -> f64 {
+        let x = self.bar.exact_x();
+        self.previous_bar_x + (x - self.previous_bar_x) * self.interpolation()
+    }
+
     /// Return whether the game is over or not
     pub fn game_over(&self) -> bool {
-        self.ball().y() >= self.dimensions.height() - 1
+        self.state == State::GameOver
     }
 
     /// Return whether the game is paused or not
     pub fn is_paused(&self) -> bool {
-        self.paused
+        self.state == State::Paused
     }
 
     /// Pause the game
+    ///
+    /// Only a game that is serving or playing can be paused.
     pub fn pause(&mut self) {
-        self.paused = true
+        if let State::Serving | State::Playing = self.state {
+            self.resume_state = self.state;
+            self.state = State::Paused
+        }
     }
 
     /// Unpause the game
     pub fn unpause(&mut self) {
-        self.paused = false
+        if self.state == State::Paused {
+            self.state = self.resume_state
+        }
     }
 
     /// Toggle the paused state of the game
     pub fn toggle_pause(&mut self) {
-        self.paused = !self.paused
+        if self.is_paused() {
+            self.unpause()
+        } else {
+            self.pause()
+        }
+    }
+}
+
+/// Check a game can be played with the config and dimensions
+fn check(width: u16, height: u16, config: &Config) -> Result<(), Error> {
+    config.validate()?;
+    check_size(width, height, config)
+}
+
+/// Check the game is big enough for the bar and ball
+fn check_size(width: u16, height: u16, config: &Config) -> Result<(), Error> {
+    if width < config.bar_width || height < MIN_HEIGHT {
+        return Err(Error::TooSmall {
+            width,
+            height,
+            min_width: config.bar_width,
+            min_height: MIN_HEIGHT,
+        });
+    }
+    Ok(())
+}
+
+/// Generate the blocks for a level.
+/// Generation of the blocks uses some padding around the game dimensions so blocks aren't too
+/// close to the edge.
+fn generate_blocks<R: Rng>(
+    width: u16,
+    height: u16,
+    density: f64,
+    strength: u16,
+    rng: &mut R,
+) -> Vec<Block> {
+    let block_dimensions = Dimensions::new(BLOCK_WIDTH, BLOCK_HEIGHT);
+    let mut blocks = Vec::new();
+    for x in ((width / 10)..(width * 9 / 10)).step_by(block_dimensions.width() as usize) {
+        if !(x + block_dimensions.width() <= (width / 2)
+            || x.saturating_sub(block_dimensions.width()) >= (width / 2))
+        {
+            continue;
+        }
+        for y in ((height / 10)..(height * 7 / 10)).step_by(block_dimensions.height() as usize) {
+            if rng.gen_bool(density) {
+                blocks.push(Block::new(
+                    Position::new(x, y),
+                    block_dimensions.clone(),
+                    strength,
+                ))
+            }
+        }
+    }
+    blocks
+}
+
+/// Pick a kind of power-up, in proportion to the weights of the kinds in `PowerUpKind::ALL`
+fn choose_power_up<R: Rng>(weights: &[f64; 6], rng: &mut R) -> PowerUpKind {
+    let mut pick = rng.gen::<f64>() * weights.iter().sum::<f64>();
+    let mut chosen = PowerUpKind::ALL[0];
+    for (&kind, &weight) in PowerUpKind::ALL.iter().zip(weights) {
+        if weight > 0. {
+            // rounding can leave the pick past the last weight, so keep the last kind that can be
+            // picked
+            chosen = kind;
+            if pick < weight {
+                break;
+            }
+            pick -= weight
+        }
+    }
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game() -> GameState {
+        GameState::new(100, 100, &Config::new(1., 1, 9, 1))
+    }
+
+    /// Add a block in the top corner, out of the ball's way, so the level isn't cleared
+    fn keep_level(game: &mut GameState) {
+        game.blocks
+            .push(Block::new(Position::new(0, 0), Dimensions::new(3, 1), 1));
+    }
+
+    fn drop_ball(game: &mut GameState) {
+        game.launch();
+        game.balls_mut()[0].set_position(Position::new(5, 95));
+        game.balls_mut()[0].set_velocity(Vector::new(0., 1.));
+        while game.state() == State::Playing {
+            game.tick();
+        }
+    }
+
+    #[test]
+    fn ball_follows_bar_while_serving() {
+        let mut game = game();
+        assert_eq!(game.state(), State::Serving);
+        assert_eq!(game.balls()[0].y() + 1, game.bar().y());
+
+        game.bar_mut().move_left();
+        game.tick();
+        assert_eq!(game.balls()[0].x(), game.bar().x() + game.bar().width() / 2);
+        assert_eq!(game.state(), State::Serving);
+
+        game.launch();
+        assert_eq!(game.state(), State::Playing);
+    }
+
+    #[test]
+    fn pause_resumes_previous_state() {
+        let mut game = game();
+        game.toggle_pause();
+        assert!(game.is_paused());
+        game.toggle_pause();
+        assert_eq!(game.state(), State::Serving);
+    }
+
+    #[test]
+    fn losing_all_lives_ends_the_game() {
+        let mut game = game();
+        drop_ball(&mut game);
+        assert_eq!(game.state(), State::LifeLost);
+        assert_eq!(game.lives(), 2);
+
+        game.tick();
+        assert_eq!(game.state(), State::Serving);
+
+        drop_ball(&mut game);
+        game.tick();
+        drop_ball(&mut game);
+        assert!(game.game_over());
+        assert_eq!(game.lives(), 0);
+    }
+
+    #[test]
+    fn clearing_a_level_advances_to_the_next() {
+        let mut game = GameState::from_seed(100, 100, &Config::new(0., 1, 9, 1), 3);
+        game.next_level();
+        assert_eq!(game.level(), 1);
+
+        game.launch();
+        game.tick();
+        assert_eq!(game.state(), State::LevelCleared);
+
+        game.next_level();
+        assert_eq!(game.level(), 2);
+        assert_eq!(game.state(), State::Serving);
+        assert_eq!(game.lives(), 3);
+        assert!(!game.blocks().is_empty());
+        assert!(game.blocks().iter().all(|block| block.strength() == 1));
+    }
+
+    #[test]
+    fn same_seed_generates_same_blocks() {
+        let config = Config::new(0.5, 1, 9, 1);
+        let layout = |game: &GameState| -> Vec<(u16, u16)> {
+            game.blocks()
+                .iter()
+                .map(|block| (block.x(), block.y()))
+                .collect()
+        };
+        let game = GameState::from_seed(100, 100, &config, 42);
+        assert_eq!(game.seed(), 42);
+        assert_eq!(
+            layout(&game),
+            layout(&GameState::from_seed(100, 100, &config, 42))
+        );
+        assert_ne!(
+            layout(&game),
+            layout(&GameState::from_seed(100, 100, &config, 43))
+        );
+    }
+
+    #[test]
+    fn checksum_includes_the_random_number_generator() {
+        let config = Config::new(0.5, 1, 9, 1);
+        let game = GameState::from_seed(100, 100, &config, 1);
+        let mut other = GameState::from_seed(100, 100, &config, 1);
+        assert_eq!(game.checksum(), other.checksum());
+        other.rng.gen::<u64>();
+        assert_ne!(game.checksum(), other.checksum());
+    }
+
+    #[test]
+    fn game_from_level() {
+        let level = Level::parse("123").unwrap();
+        let game = GameState::from_level(100, 100, &Config::new(0., 1, 9, 1), &level);
+        let strengths: Vec<_> = game.blocks().iter().map(|block| block.strength()).collect();
+        assert_eq!(strengths, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn levels_with_seeds_are_reproducible() {
+        let level = Level::parse("5P5\nR.X").unwrap();
+        let config = Config {
+            power_up_chance: 1.,
+            ..Config::new(0., 1, 9, 1)
+        };
+        let play = || {
+            let mut game = GameState::from_level_with_seed(30, 30, &config, &level, 7);
+            game.launch();
+            for _ in 0..200 {
+                game.tick();
+            }
+            (game.seed(), game.checksum())
+        };
+        assert_eq!(play(), play());
+        assert_eq!(play().0, 7);
+    }
+
+    #[test]
+    fn infallible_constructors_clamp_the_config() {
+        let mut config = Config::new(1.5, 0, 5, 1);
+        config.lives = 0;
+        config.ball_speed = f64::NAN;
+        let game = GameState::from_seed(20, 20, &config, 1);
+        assert_eq!(game.lives(), 1);
+        assert!(game.blocks().iter().all(|block| block.strength() == 1));
+        assert!(GameState::try_from_seed(20, 20, &config, 1).is_err());
+    }
+
+    #[test]
+    fn invalid_games_are_errors() {
+        let config = Config::new(1., 1, 5, 1);
+        let too_small = Error::TooSmall {
+            width: 4,
+            height: 6,
+            min_width: 5,
+            min_height: 7,
+        };
+        assert_eq!(GameState::try_new(4, 6, &config).err(), Some(too_small));
+        let invalid = Config::new(1.5, 1, 5, 1);
+        assert!(matches!(
+            GameState::try_new(20, 20, &invalid),
+            Err(Error::InvalidConfig {
+                field: "block_density",
+                ..
+            })
+        ));
+
+        // the smallest games still play, with the blocks clear of the ball and the bar
+        for &(width, height) in &[(5, 7), (6, 8), (12, 7)] {
+            let mut game = GameState::try_new(width, height, &config).unwrap();
+            for block in game.blocks() {
+                assert!(block.y() + block.height() < game.bar().y());
+            }
+            game.launch();
+            for _ in 0..100 {
+                game.tick()
+            }
+        }
+    }
+
+    #[test]
+    fn ball_bounces_more_than_once_per_tick() {
+        let mut game = GameState::new(100, 100, &Config::new(0., 1, 9, 1));
+        game.launch();
+        game.balls_mut()[0].set_position(Position::from_exact(0.25, 0.25));
+        game.balls_mut()[0].set_velocity(Vector::new(-1., -1.));
+        game.move_ball(0);
+        let velocity = game.balls()[0].velocity();
+        assert!(velocity.x() > 0. && (velocity.x() - velocity.y()).abs() < 1e-9);
+        assert!(game.balls()[0].position().exact_x() > 0.);
+        assert!(game.balls()[0].position().exact_y() > 0.);
+    }
+
+    #[test]
+    fn ball_stays_outside_blocks() {
+        let mut game = GameState::from_seed(80, 40, &Config::new(0.8, 2, 9, 1), 1);
+        game.launch();
+        game.balls_mut()[0].set_velocity(Vector::new(1., -2.));
+        for _ in 0..2000 {
+            let ball_x = game.balls()[0].x();
+            while game.bar().x() + game.bar().width() / 2 < ball_x {
+                game.bar_mut().move_right()
+            }
+            while game.bar().x() + game.bar().width() / 2 > ball_x && game.bar().x() > 0 {
+                game.bar_mut().move_left()
+            }
+            game.tick();
+            if game.state() != State::Playing {
+                break;
+            }
+            let ball = &game.balls()[0];
+            assert!(ball.x() < game.width() && ball.y() < game.height());
+            assert!(!game.blocks().iter().any(|block| {
+                block.x() <= ball.x()
+                    && ball.x() < block.x() + block.width()
+                    && block.y() <= ball.y()
+                    && ball.y() < block.y() + block.height()
+            }));
+        }
+    }
+
+    #[test]
+    fn balls_inside_blocks_are_pushed_out() {
+        let mut game = GameState::new(100, 100, &Config::new(0., 1, 9, 1));
+        game.blocks
+            .push(Block::new(Position::new(10, 10), Dimensions::new(3, 1), 2));
+        game.launch();
+        // the ball is nearer the top of the block, so it is pushed out the top and hits it
+        game.balls_mut()[0].set_position(Position::from_exact(11.5, 10.4));
+        game.balls_mut()[0].set_velocity(Vector::new(0., 1.));
+        game.tick();
+        assert_eq!(game.blocks()[0].strength(), 1);
+        assert!(game.balls()[0].exact_y() < 10.);
+        assert!(game.balls()[0].velocity().y() < 0.);
+    }
+
+    #[test]
+    fn life_lost_with_the_last_ball() {
+        let mut game = GameState::new(100, 100, &Config::new(1., 1, 9, 1));
+        game.launch();
+        game.spawn_ball(Position::new(5, 90), Vector::new(0., 1.));
+        assert_eq!(game.balls().len(), 2);
+        while game.balls().len() == 2 {
+            game.tick();
+        }
+        assert_eq!(game.state(), State::Playing);
+
+        game.balls_mut()[0].set_position(Position::new(95, 95));
+        game.balls_mut()[0].set_velocity(Vector::new(0., 1.));
+        while game.state() == State::Playing {
+            game.tick();
+        }
+        assert_eq!(game.state(), State::LifeLost);
+        assert!(game.balls().is_empty());
+
+        game.tick();
+        assert_eq!(game.balls().len(), 1);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn single_ball_accessors_use_the_first_ball() {
+        let mut game = game();
+        game.ball_mut().set_speed(2.);
+        assert_eq!(game.ball().speed(), 2.);
+        assert_eq!(game.balls()[0].speed(), 2.);
+    }
+
+    #[test]
+    fn resizing_too_small_is_an_error() {
+        let mut game = game();
+        assert!(game.try_update_dimensions(8, 50).is_err());
+        assert_eq!(game.width(), 100);
+        game.try_update_dimensions(9, 7).unwrap();
+        assert_eq!((game.bar().x(), game.bar().y()), (0, 5));
+    }
+
+    #[test]
+    fn resizes_round_trip() {
+        let policies = [
+            ResizePolicy::Keep,
+            ResizePolicy::Clamp,
+            ResizePolicy::Scale,
+            ResizePolicy::Centre,
+        ];
+        for &policy in &policies {
+            let mut game = GameState::from_seed(80, 40, &Config::new(0.5, 1, 9, 1), 5);
+            game.set_resize_policy(policy);
+            game.launch();
+            for _ in 0..5 {
+                game.tick()
+            }
+            let blocks: Vec<_> = game.blocks().iter().map(|b| (b.x(), b.y())).collect();
+            let ball = game.balls()[0].position().clone();
+            let bar = game.bar().exact_x();
+
+            for &(width, height) in &[(50, 25), (120, 60), (80, 40)] {
+                game.update_dimensions(width, height);
+                if policy != ResizePolicy::Keep {
+                    assert!(game.blocks().iter().all(|block| {
+                        block.x() + block.width() <= width && block.y() + block.height() <= height
+                    }));
+                }
+            }
+
+            let resized: Vec<_> = game.blocks().iter().map(|b| (b.x(), b.y())).collect();
+            assert_eq!(resized, blocks, "{:?}", policy);
+            assert!((game.bar().exact_x() - bar).abs() < 1e-9, "{:?}", policy);
+            // the other policies move the ball up to stay above the bar in the shorter game
+            if policy == ResizePolicy::Scale {
+                let position = game.balls()[0].position();
+                assert!((position.exact_x() - ball.exact_x()).abs() < 1e-9);
+                assert!((position.exact_y() - ball.exact_y()).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn centre_policy_moves_everything_together() {
+        let mut game = GameState::from_seed(80, 40, &Config::new(0.5, 1, 9, 1), 5);
+        game.set_resize_policy(ResizePolicy::Centre);
+        game.launch();
+        let blocks: Vec<_> = game.blocks().iter().map(Block::x).collect();
+        let (ball, bar) = (game.balls()[0].x(), game.bar().x());
+        game.update_dimensions(100, 40);
+        let moved: Vec<_> = game.blocks().iter().map(|block| block.x() - 10).collect();
+        assert_eq!(moved, blocks);
+        assert_eq!((game.balls()[0].x(), game.bar().x()), (ball + 10, bar + 10));
+    }
+
+    #[test]
+    fn resizing_resets_interpolation() {
+        let mut game = GameState::from_seed(80, 40, &Config::new(0.5, 1, 9, 1), 5);
+        game.set_resize_policy(ResizePolicy::Centre);
+        game.launch();
+        game.bar_mut().set_input(1.);
+        game.advance(Duration::from_millis(175));
+        assert!(game.interpolated_bar_x() < game.bar().exact_x());
+
+        game.update_dimensions(200, 40);
+        assert_eq!(game.interpolated_bar_x(), game.bar().exact_x());
+        let ball = game.balls()[0].position();
+        assert_eq!(
+            game.interpolated_balls()[0],
+            (ball.exact_x(), ball.exact_y())
+        );
+    }
+
+    #[test]
+    fn advance_runs_fixed_ticks() {
+        let mut game = game();
+        game.launch();
+        let step = Duration::from_millis(20);
+        assert_eq!(game.tick_length(), Duration::from_millis(50));
+        assert_eq!(game.advance(step), 0);
+        assert_eq!(game.advance(step), 0);
+        assert!((game.interpolation() - 0.8).abs() < 1e-9);
+        assert_eq!(game.advance(step), 1);
+        assert!((game.interpolation() - 0.2).abs() < 1e-9);
+        assert_eq!(game.advance(Duration::from_millis(110)), 2);
+
+        // a long pause only runs a few ticks
+        assert_eq!(game.advance(Duration::from_secs(10)), MAX_TICKS_PER_ADVANCE);
+        assert_eq!(game.interpolation(), 0.);
+    }
+
+    #[test]
+    fn interpolate_between_ticks() {
+        let mut game = game();
+        game.launch();
+        game.balls_mut()[0].set_position(Position::new(50, 50));
+        game.balls_mut()[0].set_velocity(Vector::new(1., 0.));
+        game.advance(Duration::from_millis(75));
+        let (x, y) = game.interpolated_balls()[0];
+        assert!((x - 50.5).abs() < 1e-9 && (y - 50.).abs() < 1e-9);
+
+        // a ball that is teleported isn't drawn sliding across from where it was
+        game.balls_mut()[0].set_position(Position::new(20, 30));
+        assert_eq!(game.interpolated_balls()[0], (20., 30.));
+
+        game.bar_mut().move_left();
+        game.advance(Duration::from_millis(50));
+        let bar_x = f64::from(game.bar().x());
+        assert!((game.interpolated_bar_x() - bar_x).abs() < 1e-9);
+        game.bar_mut().move_left();
+        game.advance(Duration::from_millis(25));
+        assert!((game.interpolated_bar_x() - (bar_x - 1.)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn moving_bar_spins_ball() {
+        let bounce = |spin| {
+            let config = Config {
+                bar_spin: spin,
+                ..Config::new(0., 1, 9, 1)
+            };
+            let mut game = GameState::new(100, 100, &config);
+            game.launch();
+            let x = game.bar().exact_x() + 4.5;
+            let y = f64::from(game.bar().y()) - 0.5;
+            game.balls_mut()[0].set_position(Position::from_exact(x, y));
+            game.balls_mut()[0].set_velocity(Vector::new(0., 1.));
+            game.bar_mut().set_input(1.);
+            game.tick();
+            assert!(game.bar().velocity() > 0.);
+            game.balls()[0].velocity().x()
+        };
+        // the bar moves right under the ball so it hits left of centre, spin turns it back right
+        assert!(bounce(0.) < 0.);
+        assert!(bounce(20.) > 0.);
+    }
+
+    #[test]
+    fn ticks_emit_events() {
+        let level = Level::parse("2").unwrap();
+        let mut game = GameState::from_level(30, 30, &Config::new(0., 1, 9, 1), &level);
+        game.launch();
+        while game.state() == State::Playing {
+            game.tick();
+        }
+        let events: Vec<_> = game.drain_events().collect();
+        assert_eq!(
+            events,
+            vec![
+                Event::BlockDamaged {
+                    x: 13,
+                    y: 3,
+                    kind: BlockKind::Normal,
+                    strength: 1
+                },
+                Event::BallHitBar {
+                    ball: 0,
+                    offset: 0.
+                },
+                Event::BlockDestroyed {
+                    x: 13,
+                    y: 3,
+                    kind: BlockKind::Normal
+                },
+                Event::LevelCleared { level: 1 },
+            ]
+        );
+        assert!(game.events().is_empty());
+    }
+
+    #[test]
+    fn explosions_chain_and_walls_remain() {
+        let level = Level::parse("1X1\nWX.\n...\n...\n..1").unwrap();
+        let mut game = GameState::from_level(30, 30, &Config::new(0., 1, 9, 1), &level);
+        let explosive = game
+            .blocks()
+            .iter()
+            .position(|block| block.kind() != BlockKind::Normal)
+            .unwrap();
+        game.hit_block(explosive, 1);
+        let kinds: Vec<_> = game.blocks().iter().map(Block::kind).collect();
+        assert_eq!(kinds, vec![BlockKind::Wall, BlockKind::Normal]);
+        assert_eq!(game.combo(), 4);
+
+        game.hit_block(1, 1);
+        game.launch();
+        game.tick();
+        assert_eq!(game.state(), State::LevelCleared);
+    }
+
+    #[test]
+    fn piercing_balls_hit_multi_hit_blocks_once() {
+        let mut game = GameState::new(100, 100, &Config::new(0., 1, 9, 1));
+        game.blocks.push(Block::with_kind(
+            Position::new(10, 10),
+            Dimensions::new(3, 1),
+            3,
+            BlockKind::MultiHit,
+        ));
+        game.effects
+            .push(Effect::new(PowerUpKind::PiercingBall, 1000));
+        game.launch();
+        game.balls_mut()[0].set_position(Position::from_exact(11.5, 9.5));
+        game.balls_mut()[0].set_velocity(Vector::new(0., 1.));
+        game.tick();
+        assert_eq!(game.blocks()[0].strength(), 2);
+        game.tick();
+        game.tick();
+        assert_eq!(game.blocks()[0].strength(), 2);
+        assert!(game.balls()[0].exact_y() > 11.);
+    }
+
+    #[test]
+    fn destroyed_blocks_drop_power_ups() {
+        let mut config = Config::new(0., 1, 9, 1);
+        config.power_up_chance = 1.;
+        let mut game = GameState::new(100, 100, &config);
+        game.blocks
+            .push(Block::new(Position::new(49, 10), Dimensions::new(3, 1), 1));
+        game.launch();
+        while game.power_ups().is_empty() {
+            game.tick();
+        }
+        assert_eq!((game.power_ups()[0].x(), game.power_ups()[0].y()), (50, 10));
+    }
+
+    #[test]
+    fn power_ups_are_picked_by_weight() {
+        let mut rng = Pcg32::seed_from_u64(1);
+        let mut weights = [0.; 6];
+        weights[3] = 1.;
+        for _ in 0..100 {
+            assert_eq!(choose_power_up(&weights, &mut rng), PowerUpKind::MultiBall);
+        }
+        weights = [1., 0., 0., 0., 0., 3.];
+        let sticky = (0..1000)
+            .filter(|_| choose_power_up(&weights, &mut rng) == PowerUpKind::StickyBar)
+            .count();
+        assert!((650..850).contains(&sticky), "{}", sticky);
+    }
+
+    #[test]
+    fn catching_power_ups() {
+        let mut config = Config::new(0., 1, 10, 1);
+        config.power_up_duration = 3;
+        let mut game = GameState::new(100, 100, &config);
+        keep_level(&mut game);
+        game.launch();
+        let x = game.bar().x();
+        for kind in &[PowerUpKind::WideBar, PowerUpKind::ExtraLife] {
+            game.power_ups
+                .push(PowerUp::new(*kind, Position::new(x, game.bar().y() - 1)));
+        }
+        for _ in 0..4 {
+            game.tick();
+        }
+        assert!(game.power_ups().is_empty());
+        assert_eq!(game.lives(), 4);
+        assert_eq!(game.bar().width(), 15);
+        assert_eq!(game.effect_remaining(PowerUpKind::WideBar), Some(3));
+
+        for _ in 0..3 {
+            game.tick();
+        }
+        assert_eq!(game.effect_remaining(PowerUpKind::WideBar), None);
+        assert_eq!(game.bar().width(), 10);
+    }
+
+    #[test]
+    fn multi_ball_copies_a_moving_ball() {
+        let mut game = GameState::new(100, 100, &Config::new(0., 1, 9, 1));
+        game.launch();
+        game.balls_mut()[0].stick(4.5);
+        game.spawn_ball(Position::new(50, 20), Vector::new(0., 1.));
+        game.apply_power_up(PowerUpKind::MultiBall);
+        assert_eq!(game.balls().len(), 4);
+        assert!(game.balls()[2..].iter().all(|ball| ball.y() == 20));
+    }
+
+    #[test]
+    fn sticky_bar_catches_the_ball() {
+        let mut game = GameState::new(100, 100, &Config::new(0., 1, 9, 1));
+        keep_level(&mut game);
+        game.effects.push(Effect::new(PowerUpKind::StickyBar, 1000));
+        game.launch();
+        while game.balls()[0].stuck_offset().is_none() {
+            game.tick();
+        }
+        game.bar_mut().move_left();
+        game.tick();
+        assert_eq!(game.balls()[0].x(), game.bar().x() + game.bar().width() / 2);
+
+        game.launch();
+        game.tick();
+        assert_eq!(game.balls()[0].stuck_offset(), None);
+        assert!(game.balls()[0].y() + 1 < game.bar().y());
+    }
+
+    #[test]
+    fn sticky_bar_releases_balls_when_it_runs_out() {
+        let mut game = GameState::new(100, 100, &Config::new(0., 1, 9, 1));
+        keep_level(&mut game);
+        game.effects.push(Effect::new(PowerUpKind::StickyBar, 1000));
+        game.launch();
+        while game.balls()[0].stuck_offset().is_none() {
+            game.tick();
+        }
+        game.effects = vec![Effect::new(PowerUpKind::StickyBar, 1)];
+        game.tick();
+        assert_eq!(game.balls()[0].stuck_offset(), None);
+        game.tick();
+        assert!(game.balls()[0].y() + 1 < game.bar().y());
+    }
+
+    #[test]
+    fn catch_launches_by_offset() {
+        let config = Config {
+            catch: true,
+            ..Config::new(0., 1, 9, 1)
+        };
+        let mut game = GameState::new(100, 100, &config);
+        keep_level(&mut game);
+        game.launch();
+        assert_eq!(game.balls()[0].velocity(), &Vector::new(0., -1.));
+
+        // drop the ball onto the right end of the bar
+        let x = game.bar().exact_x() + 8.;
+        game.balls_mut()[0].set_position(Position::from_exact(x, 90.));
+        game.balls_mut()[0].set_velocity(Vector::new(0., 1.));
+        while game.balls()[0].stuck_offset().is_none() {
+            game.tick();
+        }
+        assert_eq!(game.balls()[0].stuck_offset(), Some(8.));
+        game.bar_mut().move_left();
+        game.tick();
+        assert_eq!(game.balls()[0].exact_x(), game.bar().exact_x() + 8.);
+
+        game.launch();
+        let velocity = game.balls()[0].velocity().clone();
+        let angle = game.bar().hit_offset(game.balls()[0].exact_x()) * 60.;
+        assert!((velocity.x() - angle.to_radians().sin()).abs() < 1e-9);
+        assert!(velocity.x() > 0. && velocity.y() < 0.);
+    }
+
+    #[test]
+    fn ball_speeds_up_and_resets() {
+        let mut config = Config::new(0., 1, 9, 1);
+        config.speed_up_interval = 2;
+        let mut game = GameState::new(100, 100, &config);
+        game.blocks.push(Block::new(
+            Position::new(0, 10),
+            Dimensions::new(100, 1),
+            100,
+        ));
+        game.launch();
+        game.tick();
+        assert_eq!(game.balls()[0].speed(), 1.);
+        game.tick();
+        assert_eq!(game.balls()[0].speed(), 1.02);
+
+        while game.combo() == 0 {
+            game.tick();
+        }
+        assert!(game.balls()[0].speed() > 1.02);
+
+        while game.bar().x() > 0 {
+            game.bar_mut().move_left()
+        }
+        while game.state() == State::Playing {
+            game.tick();
+        }
+        game.tick();
+        assert_eq!(game.balls()[0].speed(), 1.);
     }
 }
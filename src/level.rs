@@ -0,0 +1,295 @@
+use crate::block::{Block, BlockKind};
+use crate::dimensions::Dimensions;
+use crate::position::Position;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The width of each block in a game
+pub(crate) const BLOCK_WIDTH: u16 = 3;
+/// The height of each block in a game
+pub(crate) const BLOCK_HEIGHT: u16 = 1;
+/// How far the explosion of an explosive block in a level reaches
+const EXPLOSION_RADIUS: u16 = 1;
+/// The number of tiers a multi-hit block in a level starts with
+const MULTI_HIT_TIERS: u16 = 3;
+/// The strength of a regenerating block in a level
+const REGENERATING_STRENGTH: u16 = 2;
+/// The number of ticks before a regenerating block in a level heals
+const REGENERATING_TICKS: u32 = 300;
+
+/// A block in a level, positioned by its column and row
+#[derive(Clone, Debug, PartialEq)]
+struct Cell {
+    column: u16,
+    row: u16,
+    strength: u16,
+    kind: BlockKind,
+}
+
+/// A hand designed level
+///
+/// Levels are written in plain text with one character per cell, each cell being the size of a
+/// block:
+///
+/// - `.` or a space is an empty cell
+/// - `1` to `9` is a normal block with that strength
+/// - `W` is an indestructible wall
+/// - `X` is an explosive block
+/// - `M` is a multi-hit block, starting with three tiers
+/// - `R` is a regenerating block
+/// - `P` is a block that always drops a power-up
+/// - lines starting with `#` are comments and are ignored
+#[derive(Clone, Debug, PartialEq)]
+pub struct Level {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Level {
+    /// Parse a level from its text format
+    pub fn parse(text: &str) -> Result<Level, ParseError> {
+        let mut width = 0;
+        let mut height = 0;
+        let mut row = 0;
+        let mut cells = Vec::new();
+        for (line_index, line) in text.lines().enumerate() {
+            let line_number = line_index + 1;
+            if line.starts_with('#') {
+                continue;
+            }
+            let line = line.trim_end();
+            for (column, character) in line.chars().enumerate() {
+                let too_large = ParseError::TooLarge {
+                    line: line_number,
+                    column: column + 1,
+                };
+                let column = u16::try_from(column).map_err(|_| too_large.clone())?;
+                let (strength, kind) = match character {
+                    '.' | ' ' => continue,
+                    '1'..='9' => (character as u16 - '0' as u16, BlockKind::Normal),
+                    'W' => (1, BlockKind::Wall),
+                    'X' => (
+                        1,
+                        BlockKind::Explosive {
+                            radius: EXPLOSION_RADIUS,
+                        },
+                    ),
+                    'M' => (MULTI_HIT_TIERS, BlockKind::MultiHit),
+                    'R' => (
+                        REGENERATING_STRENGTH,
+                        BlockKind::Regenerating {
+                            ticks: REGENERATING_TICKS,
+                        },
+                    ),
+                    'P' => (1, BlockKind::PowerUp),
+                    _ => {
+                        return Err(ParseError::InvalidCell {
+                            line: line_number,
+                            column: usize::from(column) + 1,
+                            character,
+                        })
+                    }
+                };
+                let row = u16::try_from(row).map_err(|_| too_large.clone())?;
+                width = width.max(column.checked_add(1).ok_or_else(|| too_large.clone())?);
+                height = height.max(row.checked_add(1).ok_or(too_large)?);
+                cells.push(Cell {
+                    column,
+                    row,
+                    strength,
+                    kind,
+                })
+            }
+            row += 1;
+        }
+        if cells.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        Ok(Level {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Get the width of the level, in blocks
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// Get the height of the level, in blocks
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Create the blocks of the level for a game with the given dimensions
+    ///
+    /// The level is centred horizontally and starts a tenth of the way down the game. Blocks
+    /// which would not fit in the top seven tenths of the game are left out.
+    pub fn blocks(&self, width: u16, height: u16) -> Vec<Block> {
+        let level_width = u32::from(self.width) * u32::from(BLOCK_WIDTH);
+        let left = (u32::from(width).saturating_sub(level_width) / 2) as u16;
+        let top = height / 10;
+        let bottom = u32::from(height) * 7 / 10;
+        self.cells
+            .iter()
+            .filter_map(|cell| {
+                let x = left.checked_add(cell.column.checked_mul(BLOCK_WIDTH)?)?;
+                let y = top.checked_add(cell.row.checked_mul(BLOCK_HEIGHT)?)?;
+                if u32::from(x) + u32::from(BLOCK_WIDTH) > u32::from(width)
+                    || u32::from(y) + u32::from(BLOCK_HEIGHT) > bottom
+                {
+                    return None;
+                }
+                Some(Block::with_kind(
+                    Position::new(x, y),
+                    Dimensions::new(BLOCK_WIDTH, BLOCK_HEIGHT),
+                    cell.strength,
+                    cell.kind,
+                ))
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Level {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Level, ParseError> {
+        Level::parse(text)
+    }
+}
+
+/// An error from parsing a level
+///
+/// Lines and columns start at 1.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseError {
+    /// A character that does not describe a cell
+    InvalidCell {
+        /// The line of the character
+        line: usize,
+        /// The column of the character
+        column: usize,
+        /// The character itself
+        character: char,
+    },
+    /// The level has more rows or columns than a game can hold
+    TooLarge {
+        /// The line the level became too large on
+        line: usize,
+        /// The column the level became too large on
+        column: usize,
+    },
+    /// The level has no blocks in it
+    Empty,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::InvalidCell {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "{}:{}: unexpected character {:?}, expected '.', ' ', 1 to 9, W, X, M, R or P",
+                line, column, character
+            ),
+            ParseError::TooLarge { line, column } => {
+                write!(f, "{}:{}: level is too large", line, column)
+            }
+            ParseError::Empty => write!(f, "level has no blocks"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_level() {
+        let level = Level::parse("# a comment\n1.2\n\n  9\n").unwrap();
+        assert_eq!(level.width(), 3);
+        assert_eq!(level.height(), 3);
+
+        let blocks = level.blocks(100, 100);
+        let layout: Vec<_> = blocks
+            .iter()
+            .map(|block| (block.x(), block.y(), block.strength()))
+            .collect();
+        assert_eq!(layout, vec![(45, 10, 1), (51, 10, 2), (51, 12, 9)]);
+    }
+
+    #[test]
+    fn parse_errors_have_positions() {
+        assert_eq!(
+            Level::parse("111\n1x1").unwrap_err(),
+            ParseError::InvalidCell {
+                line: 2,
+                column: 2,
+                character: 'x'
+            }
+        );
+        assert_eq!(
+            Level::parse("111\n0").unwrap_err().to_string(),
+            "2:1: unexpected character '0', expected '.', ' ', 1 to 9, W, X, M, R or P"
+        );
+        assert_eq!(
+            Level::parse("# nothing\n...").unwrap_err(),
+            ParseError::Empty
+        );
+    }
+
+    #[test]
+    fn parse_special_blocks() {
+        let level = Level::parse("WXMRP").unwrap();
+        let kinds: Vec<_> = level.blocks(100, 100).iter().map(Block::kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                BlockKind::Wall,
+                BlockKind::Explosive { radius: 1 },
+                BlockKind::MultiHit,
+                BlockKind::Regenerating { ticks: 300 },
+                BlockKind::PowerUp,
+            ]
+        );
+    }
+
+    #[test]
+    fn blocks_outside_the_game_are_left_out() {
+        let level = Level::parse("1111\n1111").unwrap();
+        assert_eq!(level.blocks(6, 2).len(), 2);
+
+        // the last column starts at the very edge of the widest game
+        let level = Level::parse(&"1".repeat(21846)).unwrap();
+        assert_eq!(level.blocks(u16::MAX, 100).len(), 21845);
+    }
+
+    #[test]
+    fn levels_too_large_are_errors() {
+        assert_eq!(
+            Level::parse(&"1".repeat(65536)).unwrap_err(),
+            ParseError::TooLarge {
+                line: 1,
+                column: 65536
+            }
+        );
+        assert_eq!(
+            Level::parse(&"1\n".repeat(65536)).unwrap_err(),
+            ParseError::TooLarge {
+                line: 65536,
+                column: 1
+            }
+        );
+        assert_eq!(Level::parse(&"1".repeat(65535)).unwrap().width(), u16::MAX);
+    }
+}
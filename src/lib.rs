@@ -3,21 +3,49 @@
 //! A library for the block breaker game.
 
 extern crate rand;
+extern crate rand_pcg;
 
+mod ai;
 mod ball;
 mod bar;
 mod block;
+mod checksum;
+mod collision;
 mod config;
 mod dimensions;
+pub mod env;
+mod error;
+mod event;
 mod gamestate;
+mod level;
 mod position;
+mod powerup;
+mod render;
+mod replay;
+mod resize;
+#[cfg(feature = "serde")]
+mod save;
+mod score;
+mod state;
 mod vector;
 
+pub use ai::{Autopilot, BarMove};
 pub use ball::Ball;
 pub use bar::Bar;
-pub use block::Block;
-pub use config::Config;
+pub use block::{Block, BlockKind};
+pub use config::{Config, ConfigBuilder, Difficulty};
 pub use dimensions::Dimensions;
+pub use error::Error;
+pub use event::Event;
 pub use gamestate::GameState;
+pub use level::{Level, ParseError};
 pub use position::Position;
+pub use powerup::{Effect, PowerUp, PowerUpKind};
+#[cfg(feature = "serde")]
+pub use replay::REPLAY_VERSION;
+pub use replay::{Divergence, Input, Recorder, Replay};
+pub use resize::ResizePolicy;
+#[cfg(feature = "serde")]
+pub use save::{SaveError, SAVE_VERSION};
+pub use state::State;
 pub use vector::Vector;
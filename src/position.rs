@@ -1,7 +1,13 @@
+use crate::checksum::{Checksum, Fnv1a};
 use crate::vector::Vector;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 
 /// A generic position
+///
+/// Positions are stored exactly, `x` and `y` give the cell the position is in while `exact_x`
+/// and `exact_y` give where in the cell it is.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
     x: f64,
     y: f64,
@@ -16,12 +22,29 @@ impl Position {
         }
     }
 
-    /// Get the x coordinate
+    /// Create a new position from exact coordinates
+    ///
+    /// The coordinates of the cell `(x, y)` run from `x` up to `x + 1` and `y` up to `y + 1`.
+    pub fn from_exact(x: f64, y: f64) -> Position {
+        Position { x, y }
+    }
+
+    /// Get the exact x coordinate
+    pub fn exact_x(&self) -> f64 {
+        self.x
+    }
+
+    /// Get the exact y coordinate
+    pub fn exact_y(&self) -> f64 {
+        self.y
+    }
+
+    /// Get the x coordinate of the cell the position is in
     pub fn x(&self) -> u16 {
         self.x as u16
     }
 
-    /// Get the y coordinate
+    /// Get the y coordinate of the cell the position is in
     pub fn y(&self) -> u16 {
         self.y as u16
     }
@@ -38,7 +61,7 @@ impl Add for Position {
     }
 }
 
-impl<'a, 'b> Add<&'b Position> for &'a Position {
+impl<'b> Add<&'b Position> for &Position {
     type Output = Position;
 
     fn add(self, other: &'b Position) -> Position {
@@ -49,7 +72,7 @@ impl<'a, 'b> Add<&'b Position> for &'a Position {
     }
 }
 
-impl<'a, 'b> Add<&'b Vector> for &'a Position {
+impl<'b> Add<&'b Vector> for &Position {
     type Output = Position;
 
     fn add(self, other: &'b Vector) -> Position {
@@ -69,7 +92,7 @@ impl AddAssign<Position> for Position {
     }
 }
 
-impl<'a> AddAssign<&'a Position> for Position {
+impl AddAssign<&Position> for Position {
     fn add_assign(&mut self, other: &Self) {
         *self = Self {
             x: self.x + other.x,
@@ -97,3 +120,10 @@ impl SubAssign for Position {
         }
     }
 }
+
+impl Checksum for Position {
+    fn checksum(&self, checksum: &mut Fnv1a) {
+        checksum.write_f64(self.x);
+        checksum.write_f64(self.y);
+    }
+}
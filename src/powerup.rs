@@ -0,0 +1,153 @@
+use crate::checksum::{Checksum, Fnv1a};
+use crate::position::Position;
+
+/// How much wider the bar is with the `WideBar` effect
+pub(crate) const WIDE_BAR_FACTOR: f64 = 1.5;
+/// How much of its speed a ball keeps with the `SlowBall` effect
+pub(crate) const SLOW_BALL_FACTOR: f64 = 0.5;
+
+/// The effect a power-up grants when the bar catches it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PowerUpKind {
+    /// The bar is wider
+    WideBar,
+    /// The balls move at half speed
+    SlowBall,
+    /// An extra life
+    ExtraLife,
+    /// Two more balls are launched
+    MultiBall,
+    /// The balls destroy blocks without bouncing off them
+    PiercingBall,
+    /// The balls stick to the bar until they are launched
+    StickyBar,
+}
+
+impl PowerUpKind {
+    /// All of the kinds of power-up
+    pub const ALL: [PowerUpKind; 6] = [
+        PowerUpKind::WideBar,
+        PowerUpKind::SlowBall,
+        PowerUpKind::ExtraLife,
+        PowerUpKind::MultiBall,
+        PowerUpKind::PiercingBall,
+        PowerUpKind::StickyBar,
+    ];
+
+    /// Whether the effect lasts for a number of ticks, rather than happening once when caught
+    pub fn is_timed(self) -> bool {
+        match self {
+            PowerUpKind::ExtraLife | PowerUpKind::MultiBall => false,
+            PowerUpKind::WideBar
+            | PowerUpKind::SlowBall
+            | PowerUpKind::PiercingBall
+            | PowerUpKind::StickyBar => true,
+        }
+    }
+}
+
+/// A power-up capsule falling towards the bar
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PowerUp {
+    kind: PowerUpKind,
+    position: Position,
+}
+
+impl PowerUp {
+    /// Create a new power-up
+    pub fn new(kind: PowerUpKind, position: Position) -> PowerUp {
+        PowerUp { kind, position }
+    }
+
+    /// Get the kind of the power-up
+    pub fn kind(&self) -> PowerUpKind {
+        self.kind
+    }
+
+    /// Get the x coordinate of the power-up
+    pub fn x(&self) -> u16 {
+        self.position.x()
+    }
+
+    /// Get the y coordinate of the power-up
+    pub fn y(&self) -> u16 {
+        self.position.y()
+    }
+
+    /// Get the position of the power-up
+    pub(crate) fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// Move the power-up to `position`
+    pub(crate) fn set_position(&mut self, position: Position) {
+        self.position = position
+    }
+
+    /// Move the power-up down by `distance`
+    pub fn fall(&mut self, distance: f64) {
+        self.position =
+            Position::from_exact(self.position.exact_x(), self.position.exact_y() + distance)
+    }
+}
+
+/// An effect from a caught power-up that lasts for a number of ticks
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Effect {
+    kind: PowerUpKind,
+    remaining: u32,
+}
+
+impl Effect {
+    /// Create a new effect lasting for `duration` ticks
+    pub fn new(kind: PowerUpKind, duration: u32) -> Effect {
+        Effect {
+            kind,
+            remaining: duration,
+        }
+    }
+
+    /// Get the kind of power-up that caused the effect
+    pub fn kind(&self) -> PowerUpKind {
+        self.kind
+    }
+
+    /// Get the number of ticks the effect has left
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// Restart the effect, lasting for `duration` more ticks
+    pub fn refresh(&mut self, duration: u32) {
+        self.remaining = self.remaining.max(duration)
+    }
+
+    /// Count down a tick, returning whether the effect has now run out
+    pub fn tick(&mut self) -> bool {
+        self.remaining = self.remaining.saturating_sub(1);
+        self.remaining == 0
+    }
+}
+
+impl Checksum for PowerUpKind {
+    fn checksum(&self, checksum: &mut Fnv1a) {
+        checksum.write_u8(*self as u8)
+    }
+}
+
+impl Checksum for PowerUp {
+    fn checksum(&self, checksum: &mut Fnv1a) {
+        self.kind.checksum(checksum);
+        self.position.checksum(checksum);
+    }
+}
+
+impl Checksum for Effect {
+    fn checksum(&self, checksum: &mut Fnv1a) {
+        self.kind.checksum(checksum);
+        checksum.write_u32(self.remaining);
+    }
+}
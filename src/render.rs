@@ -0,0 +1,111 @@
+use crate::block::BlockKind;
+use crate::gamestate::GameState;
+
+/// The character for a cell with nothing in it
+const EMPTY: char = '.';
+/// The character for the corners of the border around the game
+const CORNER: char = '+';
+/// The character for the top and bottom of the border around the game
+const HORIZONTAL: char = '-';
+/// The character for the sides of the border around the game
+const VERTICAL: char = '|';
+/// The character for a falling power-up
+const POWER_UP: char = '*';
+/// The character for the bar
+const BAR: char = '=';
+/// The character for a ball
+const BALL: char = 'o';
+
+impl GameState {
+    /// Render the game as plain text, one line per row of the game inside a border of `+`, `-`
+    /// and `|`
+    ///
+    /// Normal blocks are drawn as their strength, capped at 9, and other blocks with the same
+    /// characters as in levels: walls as `W`, explosive blocks as `X`, multi-hit blocks as `M`,
+    /// regenerating blocks as `R` and power-up blocks as `P`. Falling power-ups are drawn as `*`,
+    /// the bar as `=`, balls as `o` and empty cells as `.`. Later items are drawn over earlier
+    /// ones in that order.
+    pub fn render(&self) -> String {
+        let width = usize::from(self.width());
+        let mut cells = vec![vec![EMPTY; width]; usize::from(self.height())];
+        let mut set = |x: u16, y: u16, character: char| {
+            if let Some(cell) = cells
+                .get_mut(usize::from(y))
+                .and_then(|row| row.get_mut(usize::from(x)))
+            {
+                *cell = character
+            }
+        };
+
+        for block in self.blocks() {
+            let character = match block.kind() {
+                BlockKind::Normal => {
+                    std::char::from_digit(u32::from(block.strength().min(9)), 10).unwrap_or(EMPTY)
+                }
+                BlockKind::Wall => 'W',
+                BlockKind::Explosive { .. } => 'X',
+                BlockKind::MultiHit => 'M',
+                BlockKind::Regenerating { .. } => 'R',
+                BlockKind::PowerUp => 'P',
+            };
+            for x in block.x()..block.x() + block.width() {
+                for y in block.y()..block.y() + block.height() {
+                    set(x, y, character)
+                }
+            }
+        }
+        for power_up in self.power_ups() {
+            set(power_up.x(), power_up.y(), POWER_UP)
+        }
+        let bar = self.bar();
+        for x in bar.x()..bar.x() + bar.width() {
+            set(x, bar.y(), BAR)
+        }
+        for ball in self.balls() {
+            set(ball.x(), ball.y(), BALL)
+        }
+
+        let border: String = std::iter::once(CORNER)
+            .chain(std::iter::repeat_n(HORIZONTAL, width))
+            .chain(std::iter::once(CORNER))
+            .collect();
+        let mut text = String::with_capacity((width + 3) * (cells.len() + 2));
+        text.push_str(&border);
+        text.push('\n');
+        for row in cells {
+            text.push(VERTICAL);
+            text.extend(row);
+            text.push(VERTICAL);
+            text.push('\n')
+        }
+        text.push_str(&border);
+        text.push('\n');
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::gamestate::GameState;
+    use crate::level::Level;
+
+    #[test]
+    fn render_game() {
+        let level = Level::parse("1W2\nXMR").unwrap();
+        let game = GameState::from_level(12, 8, &Config::new(0., 1, 4, 1), &level);
+        assert_eq!(
+            game.render(),
+            "+------------+\n\
+             |.111WWW222..|\n\
+             |.XXXMMMRRR..|\n\
+             |............|\n\
+             |............|\n\
+             |............|\n\
+             |......o.....|\n\
+             |....====....|\n\
+             |............|\n\
+             +------------+\n"
+        );
+    }
+}
@@ -0,0 +1,458 @@
+use crate::config::Config;
+use crate::event::Event;
+use crate::gamestate::GameState;
+use std::error::Error;
+use std::fmt;
+
+/// The inputs given to the game for a single tick
+///
+/// The bar is driven through `Bar::set_input`, with the digital controls adding full speed left
+/// or right to the analog input, or through `Bar::move_to` when it is given a target.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Input {
+    /// Move the bar left
+    pub left: bool,
+    /// Move the bar right
+    pub right: bool,
+    /// The analog input for the bar, from -1 for full speed left to 1 for full speed right
+    ///
+    /// Values outside that range are clamped and values that aren't numbers are treated as 0.
+    pub analog: f64,
+    /// The point to move the centre of the bar towards, instead of using the other controls
+    ///
+    /// Targets that aren't finite are ignored.
+    pub target: Option<f64>,
+    /// Toggle the paused state
+    pub pause: bool,
+    /// Launch the ball
+    pub launch: bool,
+}
+
+impl Input {
+    /// Apply the input to the game, this doesn't tick the game
+    pub fn apply(self, game: &mut GameState) {
+        let input = self.sanitised();
+        if input.pause {
+            game.toggle_pause()
+        }
+        if !game.is_paused() {
+            match input.target {
+                Some(x) => game.bar_mut().move_to(x),
+                None => {
+                    let digital = f64::from(input.right as u8) - f64::from(input.left as u8);
+                    game.bar_mut().set_input(input.analog + digital)
+                }
+            }
+            if input.launch {
+                game.launch()
+            }
+        }
+    }
+
+    /// Get the input as it is applied, with the analog input clamped and targets that aren't
+    /// finite left out, so it can be saved
+    fn sanitised(self) -> Input {
+        Input {
+            analog: if self.analog.is_nan() {
+                0.
+            } else {
+                self.analog.clamp(-1., 1.)
+            },
+            target: self.target.filter(|x| x.is_finite()),
+            ..self
+        }
+    }
+
+    /// Encode the digital controls of the input as a single hexadecimal digit
+    pub fn to_char(self) -> char {
+        let bits = self.left as u32
+            | (self.right as u32) << 1
+            | (self.pause as u32) << 2
+            | (self.launch as u32) << 3;
+        std::char::from_digit(bits, 16).unwrap_or('0')
+    }
+
+    /// Decode the digital controls of an input from the hexadecimal digit given by `to_char`
+    pub fn from_char(character: char) -> Option<Input> {
+        let bits = character.to_digit(16)?;
+        Some(Input {
+            left: bits & 1 != 0,
+            right: bits & 2 != 0,
+            pause: bits & 4 != 0,
+            launch: bits & 8 != 0,
+            ..Input::default()
+        })
+    }
+}
+
+/// A recording of a game
+///
+/// Stores everything needed to recreate the game along with the input and a checksum of the game
+/// for every tick.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    seed: u64,
+    width: u16,
+    height: u16,
+    config: Config,
+    inputs: Vec<Input>,
+    checksums: Vec<u64>,
+}
+
+impl Replay {
+    /// Get the seed of the recorded game
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Get the width of the recorded game
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// Get the height of the recorded game
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Get the config of the recorded game
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Get the inputs for each tick
+    pub fn inputs(&self) -> &[Input] {
+        &self.inputs
+    }
+
+    /// Get the checksum of the game after each tick
+    pub fn checksums(&self) -> &[u64] {
+        &self.checksums
+    }
+
+    /// Create the game as it was before the first tick
+    pub fn new_game(&self) -> GameState {
+        GameState::from_seed(self.width, self.height, &self.config, self.seed)
+    }
+
+    /// Replay the whole recording, returning the game after the last tick
+    ///
+    /// Only the events from the last tick are left in the game.
+    pub fn play(&self) -> GameState {
+        let mut game = self.new_game();
+        for input in &self.inputs {
+            game.drain_events();
+            input.apply(&mut game);
+            game.tick();
+        }
+        game
+    }
+
+    /// Replay the recording, checking the game matches the recorded checksum after each tick
+    pub fn verify(&self) -> Result<(), Divergence> {
+        let mut game = self.new_game();
+        for (tick, (input, expected)) in self.inputs.iter().zip(&self.checksums).enumerate() {
+            game.drain_events();
+            input.apply(&mut game);
+            game.tick();
+            let actual = game.checksum();
+            if actual != *expected {
+                return Err(Divergence {
+                    tick,
+                    expected: *expected,
+                    actual,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The first tick where a replay no longer matches its recording
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// The index of the tick, starting at 0
+    pub tick: usize,
+    /// The checksum that was recorded for the tick
+    pub expected: u64,
+    /// The checksum the replay had after the tick
+    pub actual: u64,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "replay diverged at tick {}: expected checksum {:016x}, got {:016x}",
+            self.tick, self.expected, self.actual
+        )
+    }
+}
+
+impl Error for Divergence {}
+
+/// Records the inputs to a game as it is played
+///
+/// The recorder owns the game so that every change to it goes through `tick`. Resizing the game
+/// is not recorded.
+pub struct Recorder {
+    game: GameState,
+    replay: Replay,
+}
+
+impl Recorder {
+    /// Start recording a new game
+    pub fn new(width: u16, height: u16, config: &Config, seed: u64) -> Recorder {
+        Recorder {
+            game: GameState::from_seed(width, height, config, seed),
+            replay: Replay {
+                seed,
+                width,
+                height,
+                config: config.clone(),
+                inputs: Vec::new(),
+                checksums: Vec::new(),
+            },
+        }
+    }
+
+    /// Retrieve the game being recorded
+    pub fn game(&self) -> &GameState {
+        &self.game
+    }
+
+    /// Apply the input to the game and tick it, recording both
+    ///
+    /// The events from the tick are drained and returned, so they don't build up over a long
+    /// recording.
+    pub fn tick(&mut self, input: Input) -> Vec<Event> {
+        let input = input.sanitised();
+        input.apply(&mut self.game);
+        self.game.tick();
+        self.replay.inputs.push(input);
+        self.replay.checksums.push(self.game.checksum());
+        self.game.drain_events().collect()
+    }
+
+    /// Retrieve the recording so far
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Stop recording, returning the recording and the game
+    pub fn finish(self) -> (Replay, GameState) {
+        (self.replay, self.game)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod file {
+    use super::{Input, Replay};
+    use crate::config::Config;
+    use crate::save::SaveError;
+    use serde::{Deserialize, Serialize};
+
+    /// The version of the replay file format written by this version of the crate
+    pub const REPLAY_VERSION: u32 = 1;
+
+    #[derive(Serialize, Deserialize)]
+    struct ReplayFile {
+        version: u32,
+        seed: u64,
+        width: u16,
+        height: u16,
+        config: Config,
+        inputs: String,
+        analog: Vec<f64>,
+        targets: Vec<Option<f64>>,
+        checksums: Vec<u64>,
+    }
+
+    #[derive(Deserialize)]
+    struct Header {
+        version: u32,
+    }
+
+    impl Replay {
+        /// Save the replay as a JSON document tagged with `REPLAY_VERSION`
+        ///
+        /// The digital controls are stored as a string with one hexadecimal digit per tick. The
+        /// analog inputs and bar targets are stored as lists with one value per tick, which are
+        /// left empty if they were never used.
+        pub fn save(&self) -> Result<String, SaveError> {
+            let analog = if self.inputs.iter().any(|input| input.analog != 0.) {
+                self.inputs.iter().map(|input| input.analog).collect()
+            } else {
+                Vec::new()
+            };
+            let targets = if self.inputs.iter().any(|input| input.target.is_some()) {
+                self.inputs.iter().map(|input| input.target).collect()
+            } else {
+                Vec::new()
+            };
+            Ok(serde_json::to_string(&ReplayFile {
+                version: REPLAY_VERSION,
+                seed: self.seed,
+                width: self.width,
+                height: self.height,
+                config: self.config.clone(),
+                inputs: self.inputs.iter().map(|input| input.to_char()).collect(),
+                analog,
+                targets,
+                checksums: self.checksums.clone(),
+            })?)
+        }
+
+        /// Load a replay saved by `save`
+        pub fn load(save: &str) -> Result<Replay, SaveError> {
+            let header: Header = serde_json::from_str(save)?;
+            if header.version != REPLAY_VERSION {
+                return Err(SaveError::UnsupportedVersion(header.version));
+            }
+            let file: ReplayFile = serde_json::from_str(save)?;
+            let invalid = |message: String| SaveError::Json(serde::de::Error::custom(message));
+            let mut inputs = file
+                .inputs
+                .chars()
+                .map(|character| {
+                    Input::from_char(character)
+                        .ok_or_else(|| invalid(format!("invalid input {:?}", character)))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            for (name, len) in &[
+                ("analog", file.analog.len()),
+                ("targets", file.targets.len()),
+            ] {
+                if *len != 0 && *len != inputs.len() {
+                    return Err(invalid(format!(
+                        "expected {} values in {}, got {}",
+                        inputs.len(),
+                        name,
+                        len
+                    )));
+                }
+            }
+            for (input, &analog) in inputs.iter_mut().zip(&file.analog) {
+                input.analog = analog
+            }
+            for (input, &target) in inputs.iter_mut().zip(&file.targets) {
+                input.target = target
+            }
+            Ok(Replay {
+                seed: file.seed,
+                width: file.width,
+                height: file.height,
+                config: file.config,
+                inputs,
+                checksums: file.checksums,
+            })
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+pub use self::file::REPLAY_VERSION;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> Replay {
+        let mut recorder = Recorder::new(60, 30, &Config::new(0.5, 1, 7, 1), 3);
+        let launch = Input {
+            launch: true,
+            ..Input::default()
+        };
+        let left = Input {
+            left: true,
+            ..Input::default()
+        };
+        recorder.tick(left);
+        recorder.tick(launch);
+        for _ in 0..50 {
+            recorder.tick(Input::default());
+        }
+        recorder.tick(left);
+        for _ in 0..5 {
+            recorder.tick(Input {
+                analog: 0.7,
+                ..Input::default()
+            });
+        }
+        for _ in 0..5 {
+            recorder.tick(Input {
+                target: Some(12.5),
+                ..Input::default()
+            });
+        }
+        // values that can't be saved are recorded as they are applied
+        recorder.tick(Input {
+            analog: f64::NAN,
+            target: Some(f64::INFINITY),
+            ..Input::default()
+        });
+        assert!(recorder.game().events().is_empty());
+        recorder.finish().0
+    }
+
+    #[test]
+    fn input_chars_round_trip() {
+        for bits in 0..16 {
+            let character = std::char::from_digit(bits, 16).unwrap();
+            assert_eq!(Input::from_char(character).unwrap().to_char(), character);
+        }
+        assert_eq!(Input::from_char('g'), None);
+    }
+
+    #[test]
+    fn digital_input_drives_the_bar() {
+        let mut game = GameState::from_seed(60, 30, &Config::new(0.5, 1, 7, 1), 3);
+        let x = game.bar().exact_x();
+        Input {
+            left: true,
+            ..Input::default()
+        }
+        .apply(&mut game);
+        game.tick();
+        // the bar has a velocity, so it spins the ball
+        assert!(game.bar().velocity() < 0.);
+        assert!((game.bar().exact_x() - (x + game.bar().velocity())).abs() < 1e-9);
+
+        Input::default().apply(&mut game);
+        assert_eq!(game.bar().input(), 0.);
+    }
+
+    #[test]
+    fn replay_matches_recording() {
+        let replay = record();
+        assert_eq!(replay.verify(), Ok(()));
+        assert_eq!(
+            replay.play().checksum(),
+            *replay.checksums().last().unwrap()
+        );
+    }
+
+    #[test]
+    fn verify_finds_first_divergence() {
+        let mut replay = record();
+        replay.inputs[0] = Input::default();
+        assert_eq!(replay.verify().unwrap_err().tick, 0);
+
+        let mut replay = record();
+        replay.checksums[10] ^= 1;
+        assert_eq!(replay.verify().unwrap_err().tick, 10);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn replay_file_round_trips() {
+        let replay = record();
+        let save = replay.save().unwrap();
+        let loaded = Replay::load(&save).unwrap();
+        assert_eq!(loaded.inputs(), replay.inputs());
+        assert!(loaded.inputs().iter().any(|input| input.analog != 0.));
+        assert!(loaded.inputs().iter().any(|input| input.target.is_some()));
+        assert_eq!(loaded.verify(), Ok(()));
+        assert_eq!(loaded.save().unwrap(), save);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::dimensions::Dimensions;
+use crate::position::Position;
+
+/// How the blocks, bar, balls and power-ups are moved when the game is resized
+///
+/// Blocks are always placed relative to where they were laid out at the start of the level, so
+/// resizing back to the original size puts them back where they started. In a game smaller than
+/// the layout, `Clamp`, `Scale` and `Centre` can move blocks on top of each other. Overlapping
+/// blocks are still separate and are hit one at a time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResizePolicy {
+    /// Leave the blocks where they were laid out, even if they end up outside the game
+    Keep,
+    /// Leave the blocks where they were laid out, moving those that would be outside the game
+    /// back inside it
+    #[default]
+    Clamp,
+    /// Move everything in proportion to the change in size
+    Scale,
+    /// Keep the blocks centred horizontally, moving everything else with them
+    Centre,
+}
+
+impl ResizePolicy {
+    /// Get where a block laid out at `origin` in a game of `layout` goes in a game of `size`
+    pub(crate) fn place_block(
+        self,
+        origin: &Position,
+        block: &Dimensions,
+        layout: &Dimensions,
+        size: &Dimensions,
+    ) -> Position {
+        let (x, y) = (i32::from(origin.x()), i32::from(origin.y()));
+        let (x, y) = match self {
+            ResizePolicy::Keep => return origin.clone(),
+            ResizePolicy::Clamp => (x, y),
+            ResizePolicy::Scale => (
+                scale(f64::from(x), layout.width(), size.width()).round() as i32,
+                scale(f64::from(y), layout.height(), size.height()).round() as i32,
+            ),
+            ResizePolicy::Centre => (x + centre_offset(layout, size), y),
+        };
+        // keep blocks above the bottom of the area blocks are generated in
+        let bottom = u32::from(size.height()) * 7 / 10;
+        let max_x = i32::from(size.width().saturating_sub(block.width()));
+        let max_y = bottom.saturating_sub(u32::from(block.height())) as i32;
+        Position::new(x.clamp(0, max_x) as u16, y.clamp(0, max_y) as u16)
+    }
+
+    /// Move the point `(x, y)` in a game of `from` to a game of `to`, when the blocks were laid out
+    /// in a game of `layout`
+    ///
+    /// The point may be outside the new game, which is left for the caller to clamp.
+    pub(crate) fn move_point(
+        self,
+        x: f64,
+        y: f64,
+        layout: &Dimensions,
+        from: &Dimensions,
+        to: &Dimensions,
+    ) -> (f64, f64) {
+        match self {
+            ResizePolicy::Keep | ResizePolicy::Clamp => (x, y),
+            ResizePolicy::Scale => (
+                scale(x, from.width(), to.width()),
+                scale(y, from.height(), to.height()),
+            ),
+            ResizePolicy::Centre => {
+                let shift = centre_offset(layout, to) - centre_offset(layout, from);
+                (x + f64::from(shift), y)
+            }
+        }
+    }
+}
+
+/// Scale a coordinate in a game `from` wide to a game `to` wide
+fn scale(value: f64, from: u16, to: u16) -> f64 {
+    value * f64::from(to) / f64::from(from.max(1))
+}
+
+/// Get how far right the blocks laid out in a game of `layout` are moved to centre them in a
+/// game of `size`
+fn centre_offset(layout: &Dimensions, size: &Dimensions) -> i32 {
+    (i32::from(size.width()) - i32::from(layout.width())) / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn place_blocks() {
+        let origin = Position::new(60, 20);
+        let block = Dimensions::new(3, 1);
+        let layout = Dimensions::new(80, 40);
+        let place = |policy: ResizePolicy, width, height| {
+            let position =
+                policy.place_block(&origin, &block, &layout, &Dimensions::new(width, height));
+            (position.x(), position.y())
+        };
+        assert_eq!(place(ResizePolicy::Keep, 40, 20), (60, 20));
+        assert_eq!(place(ResizePolicy::Clamp, 40, 20), (37, 13));
+        assert_eq!(place(ResizePolicy::Scale, 40, 20), (30, 10));
+        assert_eq!(place(ResizePolicy::Centre, 40, 20), (37, 13));
+        assert_eq!(place(ResizePolicy::Centre, 100, 40), (70, 20));
+        for &policy in &[
+            ResizePolicy::Keep,
+            ResizePolicy::Clamp,
+            ResizePolicy::Scale,
+            ResizePolicy::Centre,
+        ] {
+            assert_eq!(place(policy, 80, 40), (60, 20));
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use crate::error::Error as GameError;
+use crate::gamestate::GameState;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+
+/// The version of the save file format written by this version of the crate
+///
+/// Saves with a different version are rejected when loading.
+pub const SAVE_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct SaveRef<'a> {
+    version: u32,
+    game: &'a GameState,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct Save {
+    game: GameState,
+}
+
+/// An error from saving or loading a game
+#[derive(Debug)]
+pub enum SaveError {
+    /// The save could not be written or read as JSON
+    Json(serde_json::Error),
+    /// The save was written with an unsupported version of its format
+    UnsupportedVersion(u32),
+    /// The saved game can't be played, e.g. its config is invalid or the bar doesn't fit in it
+    Invalid(GameError),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Json(error) => write!(f, "invalid save: {}", error),
+            SaveError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {}", version)
+            }
+            SaveError::Invalid(error) => write!(f, "invalid save: {}", error),
+        }
+    }
+}
+
+impl Error for SaveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SaveError::Json(error) => Some(error),
+            SaveError::UnsupportedVersion(_) => None,
+            SaveError::Invalid(error) => Some(error),
+        }
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(error: serde_json::Error) -> SaveError {
+        SaveError::Json(error)
+    }
+}
+
+impl GameState {
+    /// Save the full state of the game, including the exact position and velocity of the ball
+    ///
+    /// The save is a JSON document tagged with `SAVE_VERSION`. Loading a save and saving it again
+    /// gives back the same save.
+    pub fn save(&self) -> Result<String, SaveError> {
+        Ok(serde_json::to_string(&SaveRef {
+            version: SAVE_VERSION,
+            game: self,
+        })?)
+    }
+
+    /// Load a game from a save created by `save`
+    ///
+    /// The game is checked the same way as by `GameState::try_new`, so a save that has been
+    /// edited into a game that can't be played is an error.
+    pub fn load(save: &str) -> Result<GameState, SaveError> {
+        let header: Header = serde_json::from_str(save)?;
+        if header.version != SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion(header.version));
+        }
+        let save: Save = serde_json::from_str(save)?;
+        save.game.validate().map_err(SaveError::Invalid)?;
+        Ok(save.game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::position::Position;
+    use crate::vector::Vector;
+
+    #[test]
+    fn save_round_trips() {
+        let mut game = GameState::from_seed(80, 40, &Config::new(0.5, 2, 9, 1), 7);
+        game.launch();
+        for _ in 0..5 {
+            game.tick();
+        }
+        let save = game.save().unwrap();
+        let loaded = GameState::load(&save).unwrap();
+        assert_eq!(loaded.save().unwrap(), save);
+        assert_eq!(loaded.seed(), 7);
+        assert_eq!(loaded.state(), game.state());
+        assert_eq!(loaded.blocks().len(), game.blocks().len());
+    }
+
+    #[test]
+    fn save_keeps_resized_blocks() {
+        let mut game = GameState::from_seed(80, 40, &Config::new(0.5, 2, 9, 1), 7);
+        game.update_dimensions(40, 20);
+        let blocks: Vec<_> = game.blocks().iter().map(|b| (b.x(), b.y())).collect();
+        assert!(game
+            .blocks()
+            .iter()
+            .any(|block| (block.x(), block.y()) != (block.origin().x(), block.origin().y())));
+
+        let loaded = GameState::load(&game.save().unwrap()).unwrap();
+        let loaded_blocks: Vec<_> = loaded.blocks().iter().map(|b| (b.x(), b.y())).collect();
+        assert_eq!(loaded_blocks, blocks);
+        assert_eq!((loaded.width(), loaded.height()), (40, 20));
+        assert_eq!(loaded.resize_policy(), game.resize_policy());
+    }
+
+    #[test]
+    fn save_keeps_sub_cell_position() {
+        let mut game = GameState::from_seed(80, 40, &Config::new(0.5, 2, 9, 1), 7);
+        game.launch();
+        game.balls_mut()[0].set_position(Position::new(10, 30));
+        game.balls_mut()[0].set_velocity(Vector::new(1., -3.));
+        game.tick();
+        let loaded = GameState::load(&game.save().unwrap()).unwrap();
+        assert_eq!(loaded.balls()[0].velocity(), game.balls()[0].velocity());
+        assert_eq!(loaded.save().unwrap(), game.save().unwrap());
+    }
+
+    #[test]
+    fn rejects_unplayable_games() {
+        let game = GameState::from_seed(80, 40, &Config::new(0.5, 2, 9, 1), 7);
+        let save: serde_json::Value = serde_json::from_str(&game.save().unwrap()).unwrap();
+        let load = |edit: &dyn Fn(&mut serde_json::Value)| {
+            let mut save = save.clone();
+            edit(&mut save["game"]);
+            GameState::load(&save.to_string())
+        };
+        assert!(load(&|_| {}).is_ok());
+        match load(&|game| game["config"]["block_density"] = 2.0.into()) {
+            Err(SaveError::Invalid(GameError::InvalidConfig {
+                field: "block_density",
+                ..
+            })) => {}
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+        match load(&|game| game["bar"]["dimensions"]["width"] = 100.into()) {
+            Err(SaveError::Invalid(GameError::TooSmall { min_width: 100, .. })) => {}
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        match GameState::load(r#"{"version": 0, "game": null}"#) {
+            Err(SaveError::UnsupportedVersion(0)) => {}
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::checksum::{Checksum, Fnv1a};
+
+/// Points awarded for each point of damage done to a block
+const POINTS_PER_DAMAGE: u64 = 10;
+/// Points awarded for destroying a block, per point of the block's original strength
+const POINTS_PER_STRENGTH: u64 = 100;
+/// The largest multiplier a combo can reach
+const MAX_MULTIPLIER: u32 = 8;
+
+/// Keeps track of the player's score and combo
+///
+/// The combo counts the blocks hit since the ball last touched the bar and multiplies the points
+/// awarded for each hit.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Score {
+    points: u64,
+    combo: u32,
+    best_combo: u32,
+}
+
+impl Score {
+    /// Create a new, empty, score
+    pub fn new() -> Score {
+        Score::default()
+    }
+
+    /// Get the number of points scored
+    pub fn points(&self) -> u64 {
+        self.points
+    }
+
+    /// Get the current combo
+    pub fn combo(&self) -> u32 {
+        self.combo
+    }
+
+    /// Get the best combo achieved so far
+    pub fn best_combo(&self) -> u32 {
+        self.best_combo
+    }
+
+    /// Get the multiplier applied to points from the current combo
+    pub fn multiplier(&self) -> u32 {
+        self.combo.clamp(1, MAX_MULTIPLIER)
+    }
+
+    /// Record a hit on a block, extending the combo
+    ///
+    /// `damage` is the amount of strength the block lost and `destroyed` holds the original
+    /// strength of the block if the hit destroyed it.
+    pub fn hit_block(&mut self, damage: u16, destroyed: Option<u16>) {
+        self.combo += 1;
+        self.best_combo = self.best_combo.max(self.combo);
+
+        let mut points = u64::from(damage) * POINTS_PER_DAMAGE;
+        if let Some(strength) = destroyed {
+            points += u64::from(strength) * POINTS_PER_STRENGTH
+        }
+        self.points += points * u64::from(self.multiplier())
+    }
+
+    /// Reset the combo, from the ball touching the bar or being lost
+    pub fn reset_combo(&mut self) {
+        self.combo = 0
+    }
+}
+
+impl Checksum for Score {
+    fn checksum(&self, checksum: &mut Fnv1a) {
+        checksum.write_u64(self.points);
+        checksum.write_u32(self.combo);
+        checksum.write_u32(self.best_combo);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combo_multiplies_points() {
+        let mut score = Score::new();
+        score.hit_block(1, None);
+        assert_eq!(score.points(), 10);
+        score.hit_block(1, Some(2));
+        assert_eq!(score.points(), 10 + 2 * 210);
+        assert_eq!(score.combo(), 2);
+
+        score.reset_combo();
+        score.hit_block(1, None);
+        assert_eq!(score.points(), 440);
+        assert_eq!(score.combo(), 1);
+        assert_eq!(score.best_combo(), 2);
+    }
+
+    #[test]
+    fn multiplier_is_capped() {
+        let mut score = Score::new();
+        for _ in 0..20 {
+            score.hit_block(0, None);
+        }
+        assert_eq!(score.multiplier(), MAX_MULTIPLIER);
+        assert_eq!(score.best_combo(), 20);
+    }
+}
@@ -0,0 +1,27 @@
+use crate::checksum::{Checksum, Fnv1a};
+
+/// The phase of the game's lifecycle
+///
+/// Frontends can use this to decide which screen to render.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum State {
+    /// The ball is sitting on the bar waiting to be launched
+    Serving,
+    /// The ball is in play
+    Playing,
+    /// The game is paused, unpausing returns to the state before the pause
+    Paused,
+    /// The ball left the bottom of the game and a life was lost, the next tick serves a new ball
+    LifeLost,
+    /// All of the blocks have been destroyed
+    LevelCleared,
+    /// There are no lives left
+    GameOver,
+}
+
+impl Checksum for State {
+    fn checksum(&self, checksum: &mut Fnv1a) {
+        checksum.write_u8(*self as u8)
+    }
+}
@@ -1,8 +1,10 @@
+use crate::checksum::{Checksum, Fnv1a};
 use crate::position::Position;
 use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
 
 /// A 2D vector
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector {
     x: f64,
     y: f64,
@@ -61,7 +63,7 @@ impl Add for Vector {
     }
 }
 
-impl<'a, 'b> Add<&'b Vector> for &'a Vector {
+impl<'b> Add<&'b Vector> for &Vector {
     type Output = Vector;
 
     fn add(self, other: &'b Vector) -> Vector {
@@ -72,7 +74,7 @@ impl<'a, 'b> Add<&'b Vector> for &'a Vector {
     }
 }
 
-impl<'a, 'b> Add<&'b Position> for &'a Vector {
+impl<'b> Add<&'b Position> for &Vector {
     type Output = Position;
 
     fn add(self, other: &'b Position) -> Position {
@@ -92,7 +94,7 @@ impl AddAssign<Vector> for Vector {
     }
 }
 
-impl<'a> AddAssign<&'a Vector> for Vector {
+impl AddAssign<&Vector> for Vector {
     fn add_assign(&mut self, other: &Self) {
         *self = Self {
             x: self.x + other.x,
@@ -142,3 +144,10 @@ impl Mul<Vector> for f64 {
         }
     }
 }
+
+impl Checksum for Vector {
+    fn checksum(&self, checksum: &mut Fnv1a) {
+        checksum.write_f64(self.x);
+        checksum.write_f64(self.y);
+    }
+}